@@ -0,0 +1,109 @@
+use crate::core::Core;
+use crate::entity::EntityKind;
+use anyhow::{anyhow, Result};
+use comfy_table::Table;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// The canonical name behind `name`, or `name` itself when it is not an alias.
+pub fn resolve(conn: &Connection, kind: EntityKind, name: &str) -> rusqlite::Result<String> {
+    Ok(conn
+        .query_row(
+            "SELECT name FROM aliases WHERE kind = ? AND entity_key(alias) = entity_key(?)",
+            params![kind.noun(), name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or_else(|| name.to_string()))
+}
+
+/// All `(alias, canonical name)` pairs of one kind, ordered by alias.
+pub fn list(conn: &Connection, kind: EntityKind) -> rusqlite::Result<Vec<(String, String)>> {
+    let mut stmt = conn.prepare("SELECT alias, name FROM aliases WHERE kind = ? ORDER BY alias")?;
+    let rows = stmt.query_map(params![kind.noun()], |row| Ok((row.get(0)?, row.get(1)?)))?;
+    rows.collect()
+}
+
+/// Makes `alias` resolve to `name` for entries logged from now on.
+pub fn add(core: &mut Core, kind: EntityKind, alias: &str, name: &str) -> Result<()> {
+    core.validate_entity_name(kind, alias)?;
+    core.validate_entity_name(kind, name)?;
+    let policy = &core.policy;
+    let (alias, name) = (policy.normalize(alias), policy.normalize(name));
+    if policy.key(&alias) == policy.key(&name) {
+        return Err(anyhow!("An alias must differ from the name it stands for"));
+    }
+
+    let (shown_alias, shown_name) = (
+        policy.format_marker(kind, &alias),
+        policy.format_marker(kind, &name),
+    );
+    let tx = core.conn.transaction()?;
+    let canonical = resolve(&tx, kind, &name)?;
+    if canonical != name {
+        return Err(anyhow!(
+            "{shown_name} is itself an alias; point {shown_alias} at {} instead",
+            policy.format_marker(kind, &canonical)
+        ));
+    }
+    let existing: Option<String> = tx
+        .query_row(
+            &format!(
+                "SELECT name FROM {} WHERE entity_key(name) = entity_key(?)",
+                kind.table()
+            ),
+            params![alias],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if existing.is_some() {
+        return Err(anyhow!(
+            "{shown_alias} is already used in entries; run \
+             `dia entity merge '{shown_alias}' '{shown_name}'` first"
+        ));
+    }
+
+    tx.execute(
+        "DELETE FROM aliases WHERE kind = ? AND entity_key(alias) = entity_key(?)",
+        params![kind.noun(), alias],
+    )?;
+    tx.execute(
+        "INSERT INTO aliases (kind, alias, name) VALUES (?, ?, ?)",
+        params![kind.noun(), alias, name],
+    )?;
+    // Aliases of the new alias follow it to the canonical name.
+    tx.execute(
+        "UPDATE aliases SET name = ? WHERE kind = ? AND entity_key(name) = entity_key(?)",
+        params![name, kind.noun(), alias],
+    )?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Deletes an alias, returning whether it existed.
+pub fn remove(core: &Core, kind: EntityKind, alias: &str) -> rusqlite::Result<bool> {
+    let removed = core.conn.execute(
+        "DELETE FROM aliases WHERE kind = ? AND entity_key(alias) = entity_key(?)",
+        params![kind.noun(), alias],
+    )?;
+    Ok(removed > 0)
+}
+
+pub fn print_aliases(core: &Core) -> Result<()> {
+    let mut table = Table::new();
+    table.set_header(vec!["Alias", "Resolves to"]);
+    for kind in EntityKind::ALL {
+        for (alias, name) in list(&core.conn, kind)? {
+            table.add_row(vec![
+                core.policy.format_marker(kind, &alias),
+                core.policy.format_marker(kind, &name),
+            ]);
+        }
+    }
+
+    if table.row_iter().count() == 0 {
+        println!("No aliases defined.");
+    } else {
+        println!("{table}");
+    }
+    Ok(())
+}
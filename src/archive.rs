@@ -0,0 +1,235 @@
+use crate::core::Core;
+use crate::duration;
+use crate::entity::{EntityKind, EntryWithRelations};
+use crate::filter::EntryFilter;
+use crate::metadata::{self, Metadata};
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, NaiveDate, Utc};
+use rusqlite::{params, params_from_iter, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+
+/// Version of the JSON archive layout written by `dia export --format json`.
+pub const ARCHIVE_VERSION: u32 = 1;
+
+/// A lossless dump of diary entries, suitable for backups and for moving a
+/// diary between machines with `dia import`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Archive {
+    pub version: u32,
+    pub entries: Vec<ArchivedEntry>,
+    /// People, projects and tags with metadata set by `dia entity set`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub entities: Vec<ArchivedEntity>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedEntry {
+    #[serde(flatten)]
+    pub entry: EntryWithRelations,
+    #[serde(default)]
+    pub last_reviewed: Option<String>,
+    #[serde(default)]
+    pub review: Option<ArchivedReview>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedEntity {
+    pub kind: EntityKind,
+    pub name: String,
+    #[serde(flatten)]
+    pub metadata: Metadata,
+}
+
+/// The spaced-repetition state stored in `review_state`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchivedReview {
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+    pub due_date: String,
+}
+
+/// What an import added (date and text of each new entry) and how many
+/// entries it skipped as duplicates.
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub imported: Vec<(NaiveDate, String)>,
+    pub duplicates: usize,
+}
+
+impl Archive {
+    /// Collects the entries matching `filter`, with their links and review
+    /// state.
+    pub fn load(core: &Core, filter: &EntryFilter) -> anyhow::Result<Self> {
+        let entries = EntryWithRelations::from_entries(&core.conn, filter.entries(&core.conn)?)?;
+
+        let (conditions, params) = filter.conditions(&core.conn)?;
+        let where_clause = if conditions.is_empty() {
+            String::new()
+        } else {
+            format!(" WHERE {}", conditions.join(" AND "))
+        };
+        let mut stmt = core.conn.prepare(&format!(
+            "SELECT e.id, e.last_reviewed, r.ease, r.interval_days, r.repetitions, r.due_date
+             FROM entries e LEFT JOIN review_state r ON r.entry_id = e.id{where_clause}"
+        ))?;
+        let mut reviews = stmt
+            .query_map(params_from_iter(params.iter()), |row| {
+                let review = match row.get::<_, Option<f64>>(2)? {
+                    Some(ease) => Some(ArchivedReview {
+                        ease,
+                        interval_days: row.get(3)?,
+                        repetitions: row.get(4)?,
+                        due_date: row.get(5)?,
+                    }),
+                    None => None,
+                };
+                Ok((row.get::<_, i64>(0)?, (row.get(1)?, review)))
+            })?
+            .collect::<rusqlite::Result<HashMap<_, _>>>()?;
+
+        let entries = entries
+            .into_iter()
+            .map(|entry| {
+                let (last_reviewed, review) = reviews.remove(&entry.id).unwrap_or_default();
+                ArchivedEntry {
+                    entry,
+                    last_reviewed,
+                    review,
+                }
+            })
+            .collect();
+
+        let mut entities = Vec::new();
+        for kind in EntityKind::ALL {
+            for (name, metadata) in metadata::load(&core.conn, kind)? {
+                entities.push(ArchivedEntity {
+                    kind,
+                    name,
+                    metadata,
+                });
+            }
+        }
+
+        Ok(Self {
+            version: ARCHIVE_VERSION,
+            entries,
+            entities,
+        })
+    }
+
+    /// Inserts the archived entries within `tx`. Entries whose date and
+    /// content hash match an existing entry (or an earlier one in the archive)
+    /// are skipped, so importing the same file twice is harmless.
+    pub fn restore(&self, tx: &Transaction) -> anyhow::Result<ImportSummary> {
+        if self.version > ARCHIVE_VERSION {
+            return Err(anyhow!(
+                "Archive version {} is newer than this version of dia supports ({})",
+                self.version,
+                ARCHIVE_VERSION
+            ));
+        }
+
+        let mut seen = existing_fingerprints(tx)?;
+        let mut summary = ImportSummary::default();
+
+        for (index, archived) in self.entries.iter().enumerate() {
+            let entry = &archived.entry;
+            let context = || format!("Entry {} in archive", index + 1);
+
+            let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").with_context(context)?;
+            if !seen.insert(fingerprint(&date.to_string(), &entry.content)) {
+                summary.duplicates += 1;
+                continue;
+            }
+
+            let created_at = DateTime::parse_from_rfc3339(&entry.created_at)
+                .with_context(context)?
+                .with_timezone(&Utc)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string();
+
+            tx.execute(
+                "INSERT INTO entries (content, date, created_at, last_reviewed, minutes)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    entry.content,
+                    date.to_string(),
+                    created_at,
+                    archived.last_reviewed,
+                    duration::minutes(&entry.content)
+                ],
+            )?;
+            let entry_id = tx.last_insert_rowid();
+
+            for (kind, names) in [
+                (EntityKind::Person, &entry.people),
+                (EntityKind::Project, &entry.projects),
+                (EntityKind::Tag, &entry.tags),
+            ] {
+                for name in names {
+                    Core::link_entity(tx, entry_id, kind, name)?;
+                }
+            }
+
+            if let Some(review) = &archived.review {
+                tx.execute(
+                    "INSERT INTO review_state (entry_id, ease, interval_days, repetitions, due_date)
+                     VALUES (?, ?, ?, ?, ?)",
+                    params![
+                        entry_id,
+                        review.ease,
+                        review.interval_days,
+                        review.repetitions,
+                        review.due_date
+                    ],
+                )?;
+            }
+
+            summary.imported.push((date, entry.content.clone()));
+        }
+
+        // Metadata is merged into existing entities, so re-importing is harmless.
+        for entity in &self.entities {
+            let id = Core::ensure_entity(tx, entity.kind, &entity.name)?;
+            let fields = entity
+                .metadata
+                .fields
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect::<Vec<_>>();
+            metadata::store(
+                tx,
+                entity.kind,
+                id,
+                &fields,
+                entity.metadata.archived.then_some(true),
+            )?;
+        }
+
+        Ok(summary)
+    }
+}
+
+/// Hash of an entry's date and (trimmed) content, used to detect duplicates.
+pub fn fingerprint(date: &str, content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    date.hash(&mut hasher);
+    content.trim().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Fingerprints of every entry already in the diary.
+pub fn existing_fingerprints(tx: &Transaction) -> rusqlite::Result<HashSet<u64>> {
+    let mut stmt = tx.prepare("SELECT date, content FROM entries")?;
+    let rows = stmt.query_map([], |row| {
+        Ok(fingerprint(
+            &row.get::<_, String>(0)?,
+            &row.get::<_, String>(1)?,
+        ))
+    })?;
+    rows.collect()
+}
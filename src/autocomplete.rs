@@ -1,45 +0,0 @@
-use rustyline::completion::{Completer, Pair};
-use rustyline::validate::{ValidationContext, ValidationResult, Validator};
-use rustyline::Context;
-use rustyline_derive::{Helper, Highlighter};
-
-#[derive(Helper, Highlighter)]
-pub struct CompleterHelper {
-    // TODO: Add state for current entities
-}
-
-impl Completer for CompleterHelper {
-    type Candidate = Pair;
-
-    fn complete(
-        &self,
-        _line: &str,
-        pos: usize,
-        _ctx: &Context<'_>,
-    ) -> rustyline::Result<(usize, Vec<Pair>)> {
-        let completions = Vec::new();
-
-        // TODO: Implement entity-based autocompletion
-        // - Detect when user is typing @ (people), % (projects), or # (tags)
-        // - Query database for matching entities
-        // - Return as completion candidates
-
-        Ok((pos, completions))
-    }
-}
-
-// Implement Validator trait which is required by Helper
-impl Validator for CompleterHelper {
-    fn validate(&self, _ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
-        Ok(ValidationResult::Valid(None))
-    }
-}
-
-// Implement Hinter trait
-impl rustyline::hint::Hinter for CompleterHelper {
-    type Hint = String;
-
-    fn hint(&self, _line: &str, _pos: usize, _ctx: &Context<'_>) -> Option<Self::Hint> {
-        None
-    }
-}
@@ -1,156 +1,343 @@
 // src/completion.rs
-use rusqlite::{Connection, params, Result as SqliteResult, Error as SqliteError};
+use crate::alias;
+use crate::config::Config;
+use crate::core::Core;
+use crate::dates;
+use crate::entity::EntityKind;
+use crate::markers::NormalizationPolicy;
+use chrono::Local;
+use rusqlite::{Connection, Result as SqliteResult};
 use rustyline::completion::{Completer, Pair};
 use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::{ValidationContext, ValidationResult, Validator};
+use rustyline::{Context, Editor};
+use rustyline_derive::Helper;
 use std::borrow::Cow::{self, Borrowed, Owned};
 
+/// A trailing backslash continues the entry on the next line.
+const CONTINUATION: char = '\\';
+
+const MAX_COMPLETIONS: usize = 10;
+
+/// Usage older than this counts half as much when ranking suggestions.
+const HALF_LIFE_DAYS: f64 = 30.0;
+
+const PERSON_COLOR: &str = "\x1b[36m";
+const PROJECT_COLOR: &str = "\x1b[33m";
+const TAG_COLOR: &str = "\x1b[32m";
+const HINT_COLOR: &str = "\x1b[90m";
+const RESET: &str = "\x1b[0m";
+
+/// An entity name together with its ranking score and lower-cased aliases.
+struct Candidate {
+    name: String,
+    score: f64,
+    aliases: Vec<String>,
+}
+
+/// Rustyline helper for entry input: completes `@people`, `%projects` and
+/// `#tags` from a cache of the database, hints the best match inline and
+/// colours entity markers.
+#[derive(Helper)]
 pub struct DiaCompleter {
-    conn: Connection,
+    people: Vec<Candidate>,
+    projects: Vec<Candidate>,
+    tags: Vec<Candidate>,
+    policy: NormalizationPolicy,
 }
 
 impl DiaCompleter {
-    pub fn new(conn: Connection) -> Self {
-        Self { conn }
+    pub fn new(conn: &Connection, policy: NormalizationPolicy) -> SqliteResult<Self> {
+        let mut completer = Self {
+            people: Vec::new(),
+            projects: Vec::new(),
+            tags: Vec::new(),
+            policy,
+        };
+        completer.refresh(conn)?;
+        Ok(completer)
     }
-    
-    fn complete_project(&self, word: &str) -> SqliteResult<Vec<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT name FROM projects WHERE name LIKE ? || '%' ORDER BY name LIMIT 10"
-        )?;
-        
-        let rows = stmt.query_map(params![word], |row| {
-            let name: String = row.get(0)?;
-            Ok(name)
-        })?;
-        
-        let mut completions = Vec::new();
-        for row in rows {
-            completions.push(row?);
-        }
-        
-        Ok(completions)
+
+    /// Reloads the entity cache, e.g. after an entry has been logged.
+    pub fn refresh(&mut self, conn: &Connection) -> SqliteResult<()> {
+        self.people = Self::load(conn, &self.policy, EntityKind::Person)?;
+        self.projects = Self::load(conn, &self.policy, EntityKind::Project)?;
+        self.tags = Self::load(conn, &self.policy, EntityKind::Tag)?;
+        Ok(())
     }
-    
-    fn complete_tag(&self, word: &str) -> SqliteResult<Vec<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT name FROM tags WHERE name LIKE ? || '%' ORDER BY name LIMIT 10"
-        )?;
-        
-        let rows = stmt.query_map(params![word], |row| {
-            let name: String = row.get(0)?;
-            Ok(name)
+
+    /// Loads all entities of one kind, best ranked first. The score is the
+    /// number of entries mentioning the entity, decayed by how long ago it was
+    /// last used.
+    fn load(
+        conn: &Connection,
+        policy: &NormalizationPolicy,
+        kind: EntityKind,
+    ) -> SqliteResult<Vec<Candidate>> {
+        let (entity_table, link_table, id_column) =
+            (kind.table(), kind.link_table(), kind.id_column());
+        let mut stmt = conn.prepare(&format!(
+            "SELECT x.name, COUNT(e.id), julianday('now') - julianday(MAX(e.date))
+             FROM {entity_table} x
+             LEFT JOIN {link_table} l ON l.{id_column} = x.id
+             LEFT JOIN entries e ON e.id = l.entry_id
+             WHERE NOT x.archived
+             GROUP BY x.id"
+        ))?;
+
+        let rows = stmt.query_map([], |row| {
+            let uses: i64 = row.get(1)?;
+            let age: Option<f64> = row.get(2)?;
+            Ok(Candidate {
+                name: row.get(0)?,
+                score: uses as f64 * 0.5f64.powf(age.unwrap_or(0.0).max(0.0) / HALF_LIFE_DAYS),
+                aliases: Vec::new(),
+            })
         })?;
-        
-        let mut completions = Vec::new();
-        for row in rows {
-            completions.push(row?);
+
+        let mut candidates = rows.collect::<SqliteResult<Vec<_>>>()?;
+        for (alias, name) in alias::list(conn, kind)? {
+            if let Some(candidate) = candidates.iter_mut().find(|c| c.name == name) {
+                candidate.aliases.push(policy.key(&alias));
+            }
         }
-        
-        Ok(completions)
+        candidates.sort_by(|a, b| {
+            b.score
+                .total_cmp(&a.score)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        Ok(candidates)
     }
-    
-    fn complete_person(&self, word: &str) -> SqliteResult<Vec<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT name FROM people WHERE name LIKE ? || '%' ORDER BY name LIMIT 10"
-        )?;
-        
-        let rows = stmt.query_map(params![word], |row| {
-            let name: String = row.get(0)?;
-            Ok(name)
-        })?;
-        
-        let mut completions = Vec::new();
-        for row in rows {
-            completions.push(row?);
+
+    fn complete_project(&self, word: &str) -> Vec<String> {
+        self.matching(&self.projects, word)
+    }
+
+    fn complete_tag(&self, word: &str) -> Vec<String> {
+        self.matching(&self.tags, word)
+    }
+
+    fn complete_person(&self, word: &str) -> Vec<String> {
+        self.matching(&self.people, word)
+    }
+
+    fn complete_marker(&self, marker: char, word: &str) -> Vec<String> {
+        match marker {
+            '%' => self.complete_project(word),
+            '#' => self.complete_tag(word),
+            '@' => self.complete_person(word),
+            _ => Vec::new(),
         }
-        
-        Ok(completions)
+    }
+
+    /// Prefix matches on names or their aliases, compared under the
+    /// normalization policy (so case-insensitive by default), best ranked
+    /// first. Only canonical names are suggested.
+    fn matching(&self, candidates: &[Candidate], word: &str) -> Vec<String> {
+        let word = self.policy.key(word.trim_start_matches('"'));
+        candidates
+            .iter()
+            .filter(|c| {
+                self.policy.key(&c.name).starts_with(&word)
+                    || c.aliases.iter().any(|alias| alias.starts_with(&word))
+            })
+            .take(MAX_COMPLETIONS)
+            .map(|c| c.name.clone())
+            .collect()
     }
 }
 
 impl Completer for DiaCompleter {
     type Candidate = Pair;
 
-    fn complete(&self, line: &str, pos: usize, _ctx: &rustyline::Context<'_>) 
-        -> Result<(usize, Vec<Pair>), ReadlineError> 
-    {
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> Result<(usize, Vec<Pair>), ReadlineError> {
         // Find the word we're completing
         let (start, word) = find_word_at_pos(line, pos);
-        
-        // If empty, return empty completions
-        if word.is_empty() {
-            return Ok((start, vec![]));
-        }
-        
+
         // Different completion based on prefix
         let completions = match word.chars().next() {
-            Some('%') => {
-                // Project completion
-                let word_without_prefix = &word[1..];
-                match self.complete_project(word_without_prefix) {
-                    Ok(projects) => projects.into_iter()
-                        .map(|p| Pair {
-                            display: p.clone(),
-                            replacement: format!("%{}", p),
-                        })
-                        .collect(),
-                    Err(_) => vec![],
-                }
-            },
-            Some('#') => {
-                // Tag completion
-                let word_without_prefix = &word[1..];
-                match self.complete_tag(word_without_prefix) {
-                    Ok(tags) => tags.into_iter()
-                        .map(|t| Pair {
-                            display: t.clone(),
-                            replacement: format!("#{}", t),
-                        })
-                        .collect(),
-                    Err(_) => vec![],
-                }
-            },
-            Some('@') => {
-                // Person completion
-                let word_without_prefix = &word[1..];
-                match self.complete_person(word_without_prefix) {
-                    Ok(people) => people.into_iter()
-                        .map(|p| Pair {
-                            display: p.clone(),
-                            replacement: format!("@{}", p),
-                        })
-                        .collect(),
-                    Err(_) => vec![],
-                }
-            },
+            Some(marker @ ('%' | '#' | '@')) => self
+                .complete_marker(marker, &word[1..])
+                .into_iter()
+                .map(|name| Pair {
+                    replacement: EntityKind::from_marker(marker)
+                        .map(|kind| self.policy.format_marker(kind, &name))
+                        .unwrap_or_default(),
+                    display: name,
+                })
+                .collect(),
             _ => vec![],
         };
-        
+
         Ok((start, completions))
     }
 }
 
 fn find_word_at_pos(line: &str, pos: usize) -> (usize, &str) {
     let line_prefix = &line[..pos];
-    
+
     // Find the start of the current word
-    let start = line_prefix.rfind(|c: char| c.is_whitespace() || c == ',')
+    let start = line_prefix
+        .rfind(|c: char| c.is_whitespace() || c == ',')
         .map(|i| i + 1)
         .unwrap_or(0);
-    
+
     // Find the end of the current word
-    let end = line[pos..].find(|c: char| c.is_whitespace() || c == ',')
+    let end = line[pos..]
+        .find(|c: char| c.is_whitespace() || c == ',')
         .map(|i| i + pos)
         .unwrap_or(line.len());
-    
+
     (start, &line[start..end.min(pos)])
 }
 
-// Interactive entry input with autocompletion
-pub fn input_with_completion(conn: &Connection, prompt: &str) -> Result<String, ReadlineError> {
-    let mut rl = rustyline::Editor::<DiaCompleter>::new()?;
-    let completer = DiaCompleter::new(conn.clone());
-    rl.set_completer(Some(completer));
-    
-    rl.readline(prompt)
+// Multi-line entries: keep reading while the line ends with a backslash
+impl Validator for DiaCompleter {
+    fn validate(&self, ctx: &mut ValidationContext) -> rustyline::Result<ValidationResult> {
+        if ctx.input().ends_with(CONTINUATION) {
+            Ok(ValidationResult::Incomplete)
+        } else {
+            Ok(ValidationResult::Valid(None))
+        }
+    }
+}
+
+// Inline hint: the rest of the best-ranked entity for the word being typed
+impl rustyline::hint::Hinter for DiaCompleter {
+    type Hint = String;
+
+    fn hint(&self, line: &str, pos: usize, _ctx: &Context<'_>) -> Option<Self::Hint> {
+        if pos < line.len() {
+            return None;
+        }
+
+        let (_, word) = find_word_at_pos(line, pos);
+        let mut chars = word.chars();
+        let marker = chars.next()?;
+        let kind = EntityKind::from_marker(marker)?;
+
+        // Compare under the normalization policy like the completer does, so
+        // `@ali` still hints the rest of `@Alice`.
+        let typed = self.policy.key(word);
+        self.complete_marker(marker, chars.as_str())
+            .into_iter()
+            .map(|name| self.policy.format_marker(kind, &name))
+            .find_map(|full| {
+                let end = full
+                    .char_indices()
+                    .map(|(i, _)| i)
+                    .find(|&i| i > 0 && self.policy.key(&full[..i]) == typed)?;
+                Some(full[end..].to_string())
+            })
+    }
+}
+
+impl Highlighter for DiaCompleter {
+    fn highlight<'l>(&self, line: &'l str, _pos: usize) -> Cow<'l, str> {
+        let markers = self.policy.extract(line);
+        if markers.is_empty() {
+            return Borrowed(line);
+        }
+
+        let mut highlighted = String::new();
+        let mut last = 0;
+        for marker in markers {
+            let color = match marker.kind {
+                EntityKind::Person => PERSON_COLOR,
+                EntityKind::Project => PROJECT_COLOR,
+                EntityKind::Tag => TAG_COLOR,
+            };
+            highlighted.push_str(&line[last..marker.start]);
+            highlighted.push_str(&format!(
+                "{}{}{}",
+                color,
+                &line[marker.start..marker.end],
+                RESET
+            ));
+            last = marker.end;
+        }
+        highlighted.push_str(&line[last..]);
+        Owned(highlighted)
+    }
+
+    fn highlight_hint<'h>(&self, hint: &'h str) -> Cow<'h, str> {
+        Owned(format!("{}{}{}", HINT_COLOR, hint, RESET))
+    }
+
+    fn highlight_char(&self, _line: &str, _pos: usize) -> bool {
+        true
+    }
+}
+
+/// Interactive logging session with entity completion. Every submitted line
+/// (or backslash-continued block of lines) becomes one entry; Ctrl-D ends the
+/// session.
+pub fn log_interactively(core: &mut Core, date: Option<&str>) -> anyhow::Result<()> {
+    // Resolve the date before anything is typed, and only once, so a session
+    // running past midnight keeps logging to the same day.
+    let today = Local::now().date_naive();
+    let date = date
+        .map(|d| dates::parse_day(d, today).map(|d| d.to_string()))
+        .transpose()?;
+
+    let completer = DiaCompleter::new(&core.conn, core.policy.clone())?;
+
+    let mut rl = Editor::<DiaCompleter, DefaultHistory>::new()?;
+    rl.set_helper(Some(completer));
+
+    let history = Config::dir()?.join("history.txt");
+    if history.exists() {
+        rl.load_history(&history)?;
+    }
+
+    println!(
+        "Logging entries (@person, %project, #tag; Tab completes). \
+         End a line with \\ to continue it, Ctrl-D to finish."
+    );
+
+    let mut logged = 0;
+    let outcome = loop {
+        match rl.readline("dia> ") {
+            Ok(line) => {
+                let entry = line.replace(&format!("{CONTINUATION}\n"), "\n");
+                let entry = entry.trim();
+                if entry.is_empty() {
+                    continue;
+                }
+                let result = (|| -> anyhow::Result<()> {
+                    rl.add_history_entry(entry)?;
+                    core.add_entry(entry, date.as_deref())?;
+                    if let Some(helper) = rl.helper_mut() {
+                        helper.refresh(&core.conn)?;
+                    }
+                    Ok(())
+                })();
+                if let Err(err) = result {
+                    break Err(err);
+                }
+                logged += 1;
+                println!("Entry logged.");
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break Ok(()),
+            Err(err) => break Err(err.into()),
+        }
+    };
+
+    // Keep what was typed even if logging failed.
+    rl.save_history(&history)?;
+    outcome?;
+    println!(
+        "Logged {} {}.",
+        logged,
+        if logged == 1 { "entry" } else { "entries" }
+    );
+    Ok(())
 }
@@ -1,3 +1,4 @@
+use crate::markers::NormalizationPolicy;
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -6,10 +7,34 @@ use std::path::PathBuf;
 #[derive(Serialize, Deserialize, Default)]
 pub struct Config {
     pub diary_db_path: PathBuf,
+    /// How entity names are read and compared
+    #[serde(default)]
+    pub entities: NormalizationPolicy,
+    /// Settings of `dia report`
+    #[serde(default)]
+    pub reports: ReportsConfig,
+}
+
+/// The `[reports]` section of `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportsConfig {
+    /// Tags that mark an entry as an achievement for `dia report brag`.
+    /// Tags nested below them count too.
+    pub achievement_tags: Vec<String>,
+}
+
+impl Default for ReportsConfig {
+    fn default() -> Self {
+        Self {
+            achievement_tags: vec!["win".to_string(), "shipped".to_string()],
+        }
+    }
 }
 
 impl Config {
-    pub fn load() -> Result<Self, anyhow::Error> {
+    /// The `dia` config directory, created on first use.
+    pub fn dir() -> Result<PathBuf, anyhow::Error> {
         let config_dir = dirs::config_dir()
             .ok_or_else(|| anyhow::anyhow!("Could not find config directory"))?
             .join("dia");
@@ -18,10 +43,17 @@ impl Config {
             fs::create_dir_all(&config_dir)?;
         }
 
+        Ok(config_dir)
+    }
+
+    pub fn load() -> Result<Self, anyhow::Error> {
+        let config_dir = Self::dir()?;
+
         let config_file = config_dir.join("config.toml");
         if !config_file.exists() {
             let default_config = Config {
                 diary_db_path: config_dir.join("diary.db"),
+                ..Default::default()
             };
             let toml = toml::to_string(&default_config)?;
             fs::write(&config_file, toml)?;
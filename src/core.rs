@@ -1,8 +1,12 @@
-use chrono::{Local, NaiveDate};
-use rusqlite::{params, Connection};
+use anyhow::anyhow;
+use chrono::{Local, NaiveDate, NaiveDateTime};
+use rusqlite::functions::FunctionFlags;
+use rusqlite::{params, Connection, OptionalExtension, Row};
 use thiserror::Error;
 
-use crate::config;
+use crate::entity::{leaf_name, parent_name, EntityKind};
+use crate::markers::NormalizationPolicy;
+use crate::{alias, config, dates, db, duration};
 
 #[derive(Error, Debug)]
 #[error(transparent)]
@@ -13,10 +17,15 @@ pub enum Error {
     Database(#[from] rusqlite::Error),
     #[error("Date parsing error: {0}")]
     DateParse(#[from] chrono::ParseError),
+    #[error("Entry {0} not found")]
+    EntryNotFound(i64),
+    #[error("{0} not found")]
+    EntityNotFound(String),
 }
 
 pub struct Core {
     pub conn: Connection,
+    pub policy: NormalizationPolicy,
 }
 
 #[derive(Debug)]
@@ -25,181 +34,576 @@ pub struct Entry {
     pub content: String,
     pub date: NaiveDate,
     pub created_at: chrono::DateTime<Local>,
+    /// Time spent, from the entry's duration markers.
+    pub minutes: Option<i64>,
+}
+
+impl Entry {
+    /// Columns expected by [`Entry::from_row`], for use in `SELECT` lists.
+    pub const COLUMNS: &'static str = "e.id, e.content, e.date, e.created_at, e.minutes";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        let date: String = row.get(2)?;
+        let created_at: String = row.get(3)?;
+        let conversion_error = |idx, e| {
+            rusqlite::Error::FromSqlConversionFailure(idx, rusqlite::types::Type::Text, Box::new(e))
+        };
+
+        Ok(Self {
+            id: row.get(0)?,
+            content: row.get(1)?,
+            date: NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|e| conversion_error(2, e))?,
+            created_at: NaiveDateTime::parse_from_str(&created_at, "%Y-%m-%d %H:%M:%S")
+                .map_err(|e| conversion_error(3, e))?
+                .and_utc()
+                .with_timezone(&Local),
+            minutes: row.get(4)?,
+        })
+    }
 }
 
 impl Core {
     pub fn init() -> Result<Self, Error> {
         let config = config::Config::load()?;
-        let conn = Connection::open(&config.diary_db_path)?;
-
-        Self::init_tables(&conn)?;
-
-        Ok(Self { conn })
-    }
-
-    fn init_tables(conn: &Connection) -> Result<(), rusqlite::Error> {
+        let mut conn = Connection::open(&config.diary_db_path)?;
         conn.execute_batch(
-            r#"
-            PRAGMA journal_mode = WAL;
-            PRAGMA foreign_keys = ON;
-
-            CREATE TABLE IF NOT EXISTS entries (
-                id INTEGER PRIMARY KEY,
-                content TEXT NOT NULL,
-                date DATE NOT NULL,
-                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-
-            CREATE TABLE IF NOT EXISTS people (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-
-            CREATE TABLE IF NOT EXISTS projects (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-
-            CREATE TABLE IF NOT EXISTS tags (
-                id INTEGER PRIMARY KEY,
-                name TEXT NOT NULL UNIQUE,
-                created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
-            );
-
-            CREATE TABLE IF NOT EXISTS entry_people (
-                entry_id INTEGER NOT NULL REFERENCES entries(id),
-                person_id INTEGER NOT NULL REFERENCES people(id),
-                PRIMARY KEY (entry_id, person_id)
-            );
-
-            CREATE TABLE IF NOT EXISTS entry_projects (
-                entry_id INTEGER NOT NULL REFERENCES entries(id),
-                project_id INTEGER NOT NULL REFERENCES projects(id),
-                PRIMARY KEY (entry_id, project_id)
-            );
+            "PRAGMA journal_mode = WAL;
+             PRAGMA foreign_keys = ON;",
+        )?;
 
-            CREATE TABLE IF NOT EXISTS entry_tags (
-                entry_id INTEGER NOT NULL REFERENCES entries(id),
-                tag_id INTEGER NOT NULL REFERENCES tags(id),
-                PRIMARY KEY (entry_id, tag_id)
-            );
+        db::migrate(&mut conn)?;
+
+        // Entity names are looked up by `entity_key(name)`, which applies the
+        // configured normalization policy inside SQL.
+        let policy = config.entities;
+        let key_policy = policy.clone();
+        conn.create_scalar_function(
+            "entity_key",
+            1,
+            FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+            move |ctx| Ok(key_policy.key(&ctx.get::<String>(0)?)),
+        )?;
 
-            CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts 
-                USING fts5(content, tokenize = 'porter unicode61');
-            "#,
-        )
+        Ok(Self { conn, policy })
     }
 
     pub fn add_entry(&mut self, content: &str, date: Option<&str>) -> Result<(), Error> {
+        let today = Local::now().date_naive();
         let date = date
-            .map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d"))
+            .map(|d| dates::parse_day(d, today))
             .transpose()?
-            .unwrap_or_else(|| Local::now().date_naive());
+            .unwrap_or(today);
 
         let tx = self.conn.transaction()?;
+        Core::insert_entry(&tx, &self.policy, content, date, None)?;
+        tx.commit()?;
+        Ok(())
+    }
 
+    /// Inserts an entry and links its entities inside an existing transaction,
+    /// returning the new id. `created_at` is a UTC timestamp and defaults to
+    /// now.
+    pub fn insert_entry(
+        tx: &rusqlite::Transaction,
+        policy: &NormalizationPolicy,
+        content: &str,
+        date: NaiveDate,
+        created_at: Option<NaiveDateTime>,
+    ) -> Result<i64, rusqlite::Error> {
         // Insert main entry
         tx.execute(
-            "INSERT INTO entries (content, date) VALUES (?, ?)",
-            params![content, date.to_string()],
+            "INSERT INTO entries (content, date, created_at)
+             VALUES (?, ?, COALESCE(?, CURRENT_TIMESTAMP))",
+            params![
+                content,
+                date.to_string(),
+                created_at.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            ],
         )?;
 
         let entry_id = tx.last_insert_rowid();
 
         // Extract and process entities
         // Using a separate method to avoid borrow issues
-        Core::process_entities(&tx, entry_id, content)?;
+        Core::process_entities(tx, policy, entry_id, content)?;
 
-        // Insert into FTS table
-        tx.execute(
-            "INSERT INTO entries_fts (rowid, content) VALUES (?, ?)",
-            params![entry_id, content],
+        Ok(entry_id)
+    }
+
+    pub fn get_entry(&self, id: i64) -> Result<Entry, Error> {
+        self.conn
+            .query_row(
+                &format!("SELECT {} FROM entries e WHERE e.id = ?", Entry::COLUMNS),
+                params![id],
+                Entry::from_row,
+            )
+            .optional()?
+            .ok_or(Error::EntryNotFound(id))
+    }
+
+    /// Replaces an entry's text (and optionally its date), re-linking its
+    /// entities. The FTS index follows via the `entries_au` trigger.
+    pub fn update_entry(
+        &mut self,
+        id: i64,
+        content: &str,
+        date: Option<&str>,
+    ) -> Result<(), Error> {
+        let date = date
+            .map(|d| dates::parse_day(d, Local::now().date_naive()))
+            .transpose()?;
+
+        let tx = self.conn.transaction()?;
+
+        let updated = tx.execute(
+            "UPDATE entries SET content = ?, date = COALESCE(?, date) WHERE id = ?",
+            params![content, date.map(|d| d.to_string()), id],
         )?;
+        if updated == 0 {
+            return Err(Error::EntryNotFound(id));
+        }
+
+        for table in ["entry_people", "entry_projects", "entry_tags"] {
+            tx.execute(
+                &format!("DELETE FROM {table} WHERE entry_id = ?"),
+                params![id],
+            )?;
+        }
+        Core::process_entities(&tx, &self.policy, id, content)?;
+        Core::remove_orphaned_entities(&tx)?;
 
         tx.commit()?;
         Ok(())
     }
 
-    fn process_entities(
+    /// Deletes an entry. Its entity links cascade, and entities no longer
+    /// referenced by any entry are removed.
+    pub fn delete_entry(&mut self, id: i64) -> Result<(), Error> {
+        let tx = self.conn.transaction()?;
+
+        let deleted = tx.execute("DELETE FROM entries WHERE id = ?", params![id])?;
+        if deleted == 0 {
+            return Err(Error::EntryNotFound(id));
+        }
+        Core::remove_orphaned_entities(&tx)?;
+
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Renames a person, project or tag. Projects and tags nested below it
+    /// are renamed along with it (`client/website` follows `client`). With
+    /// `rewrite_text`, markers in the text of linked entries are renamed too;
+    /// returns how many entries were rewritten.
+    pub fn rename_entity(
+        &mut self,
+        kind: EntityKind,
+        name: &str,
+        new_name: &str,
+        rewrite_text: bool,
+    ) -> Result<usize, Error> {
+        self.validate_entity_name(kind, new_name)?;
+        let new_name = &self.policy.normalize(new_name);
+        let tx = self.conn.transaction()?;
+
+        let id = Core::require_entity(&tx, kind, name)?;
+        let rewritten = Core::rename_in(&tx, &self.policy, kind, id, name, new_name, rewrite_text)?;
+        Core::remove_orphaned_entities(&tx)?;
+
+        tx.commit()?;
+        Ok(rewritten)
+    }
+
+    fn rename_in(
         tx: &rusqlite::Transaction,
-        entry_id: i64,
-        content: &str,
-    ) -> Result<(), rusqlite::Error> {
-        // Extract people (@name), projects (%name), and tags (#name)
-        let people_regex = regex::Regex::new(r"@([\w-]+)").unwrap();
-        let projects_regex = regex::Regex::new(r"%([\w-]+)").unwrap();
-        let tags_regex = regex::Regex::new(r"#([\w-]+)").unwrap();
+        policy: &NormalizationPolicy,
+        kind: EntityKind,
+        id: i64,
+        name: &str,
+        new_name: &str,
+        rewrite_text: bool,
+    ) -> Result<usize, Error> {
+        // A name differing only in case or normalization is the same entity.
+        if Core::entity_id(tx, kind, new_name)?.is_some_and(|other| other != id) {
+            return Err(anyhow!(
+                "{m}{} already exists; use `dia entity merge {m}{} {m}{}` to combine them",
+                new_name,
+                name,
+                new_name,
+                m = kind.marker()
+            )
+            .into());
+        }
+        if policy
+            .key(new_name)
+            .starts_with(&format!("{}/", policy.key(name)))
+        {
+            return Err(anyhow!("Cannot move {m}{} below itself", name, m = kind.marker()).into());
+        }
 
-        // Process people
-        for cap in people_regex.captures_iter(content) {
-            let name = &cap[1];
+        tx.execute(
+            &format!("UPDATE {} SET name = ? WHERE id = ?", kind.table()),
+            params![new_name, id],
+        )?;
+        Core::attach_to_parent(tx, kind, id, new_name)?;
+        Core::repoint_aliases(tx, kind, name, new_name)?;
+        let mut rewritten = if rewrite_text {
+            Core::rewrite_markers(tx, policy, kind, id, name, new_name)?
+        } else {
+            0
+        };
+
+        for (child_id, child) in Core::children(tx, kind, id)? {
+            let target = format!("{}/{}", new_name, leaf_name(&child));
+            rewritten +=
+                Core::rename_in(tx, policy, kind, child_id, &child, &target, rewrite_text)?;
+        }
 
-            // Insert or get person
-            tx.execute(
-                "INSERT OR IGNORE INTO people (name) VALUES (?)",
-                params![name],
-            )?;
+        Ok(rewritten)
+    }
 
-            let person_id = tx.query_row(
-                "SELECT id FROM people WHERE name = ?",
-                params![name],
-                |row| row.get::<_, i64>(0),
-            )?;
+    /// Moves every link of `from` onto `into` and deletes `from`. Projects
+    /// and tags nested below `from` move below `into`, merging with any
+    /// namesakes there. With `rewrite_text`, markers in the affected entries
+    /// are rewritten as for [`Core::rename_entity`].
+    pub fn merge_entities(
+        &mut self,
+        kind: EntityKind,
+        from: &str,
+        into: &str,
+        rewrite_text: bool,
+    ) -> Result<usize, Error> {
+        let tx = self.conn.transaction()?;
 
-            // Create relationship
-            tx.execute(
-                "INSERT OR IGNORE INTO entry_people (entry_id, person_id) VALUES (?, ?)",
-                params![entry_id, person_id],
-            )?;
+        let from_id = Core::require_entity(&tx, kind, from)?;
+        let into_id = Core::require_entity(&tx, kind, into)?;
+        if from_id == into_id {
+            return Err(anyhow!("Cannot merge {}{} into itself", kind.marker(), from).into());
+        }
+        if self
+            .policy
+            .key(into)
+            .starts_with(&format!("{}/", self.policy.key(from)))
+        {
+            return Err(anyhow!(
+                "Cannot merge {m}{} into {m}{}, which is nested below it",
+                from,
+                into,
+                m = kind.marker()
+            )
+            .into());
         }
 
-        // Process projects
-        for cap in projects_regex.captures_iter(content) {
-            let name = &cap[1];
+        let rewritten = Core::merge_in(
+            &tx,
+            &self.policy,
+            kind,
+            (from_id, from),
+            (into_id, into),
+            rewrite_text,
+        )?;
+        Core::remove_orphaned_entities(&tx)?;
 
-            // Insert or get project
-            tx.execute(
-                "INSERT OR IGNORE INTO projects (name) VALUES (?)",
-                params![name],
-            )?;
+        tx.commit()?;
+        Ok(rewritten)
+    }
 
-            let project_id = tx.query_row(
-                "SELECT id FROM projects WHERE name = ?",
-                params![name],
-                |row| row.get::<_, i64>(0),
-            )?;
+    fn merge_in(
+        tx: &rusqlite::Transaction,
+        policy: &NormalizationPolicy,
+        kind: EntityKind,
+        (from_id, from): (i64, &str),
+        (into_id, into): (i64, &str),
+        rewrite_text: bool,
+    ) -> Result<usize, Error> {
+        let mut rewritten = if rewrite_text {
+            Core::rewrite_markers(tx, policy, kind, from_id, from, into)?
+        } else {
+            0
+        };
+
+        let (link_table, id_column) = (kind.link_table(), kind.id_column());
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {link_table} (entry_id, {id_column})
+                 SELECT entry_id, ? FROM {link_table} WHERE {id_column} = ?"
+            ),
+            params![into_id, from_id],
+        )?;
+        // Fields `into` lacks are taken from `from`; the rest go with it.
+        tx.execute(
+            "INSERT OR IGNORE INTO entity_metadata (kind, entity_id, key, value)
+             SELECT kind, ?, key, value FROM entity_metadata WHERE kind = ? AND entity_id = ?",
+            params![into_id, kind.noun(), from_id],
+        )?;
+        Core::repoint_aliases(tx, kind, from, into)?;
+
+        for (child_id, child) in Core::children(tx, kind, from_id)? {
+            let target = format!("{}/{}", into, leaf_name(&child));
+            rewritten += match Core::entity_id(tx, kind, &target)? {
+                Some(target_id) => Core::merge_in(
+                    tx,
+                    policy,
+                    kind,
+                    (child_id, &child),
+                    (target_id, &target),
+                    rewrite_text,
+                )?,
+                None => Core::rename_in(tx, policy, kind, child_id, &child, &target, rewrite_text)?,
+            };
+        }
 
-            // Create relationship
-            tx.execute(
-                "INSERT OR IGNORE INTO entry_projects (entry_id, project_id) VALUES (?, ?)",
-                params![entry_id, project_id],
-            )?;
+        // The remaining links to `from` cascade.
+        tx.execute(
+            &format!("DELETE FROM {} WHERE id = ?", kind.table()),
+            params![from_id],
+        )?;
+        Ok(rewritten)
+    }
+
+    /// The `(id, name)` of the projects or tags directly below `id`.
+    fn children(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        id: i64,
+    ) -> Result<Vec<(i64, String)>, rusqlite::Error> {
+        if !kind.is_hierarchical() {
+            return Ok(Vec::new());
         }
+        let mut stmt = tx.prepare(&format!(
+            "SELECT id, name FROM {} WHERE parent_id = ? ORDER BY id",
+            kind.table()
+        ))?;
+        let rows = stmt.query_map(params![id], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    }
 
-        // Process tags
-        for cap in tags_regex.captures_iter(content) {
-            let name = &cap[1];
+    /// Points a project or tag at the parent its name implies, creating the
+    /// parent if needed.
+    fn attach_to_parent(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        id: i64,
+        name: &str,
+    ) -> Result<(), rusqlite::Error> {
+        if !kind.is_hierarchical() {
+            return Ok(());
+        }
+        let parent_id = parent_name(name)
+            .map(|parent| Core::ensure_entity(tx, kind, parent))
+            .transpose()?;
+        tx.execute(
+            &format!("UPDATE {} SET parent_id = ? WHERE id = ?", kind.table()),
+            params![parent_id, id],
+        )?;
+        Ok(())
+    }
 
-            // Insert or get tag
-            tx.execute(
-                "INSERT OR IGNORE INTO tags (name) VALUES (?)",
-                params![name],
-            )?;
+    fn repoint_aliases(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        old: &str,
+        new: &str,
+    ) -> Result<(), rusqlite::Error> {
+        tx.execute(
+            "UPDATE aliases SET name = ? WHERE kind = ? AND entity_key(name) = entity_key(?)",
+            params![new, kind.noun(), old],
+        )?;
+        Ok(())
+    }
 
-            let tag_id =
-                tx.query_row("SELECT id FROM tags WHERE name = ?", params![name], |row| {
-                    row.get::<_, i64>(0)
-                })?;
+    /// Checks that `name` can be written as a marker, quoted if need be.
+    pub fn validate_entity_name(&self, kind: EntityKind, name: &str) -> Result<(), Error> {
+        if self.policy.is_valid_name(kind, name) {
+            Ok(())
+        } else if kind.is_hierarchical() && name.contains('/') && !name.contains('"') {
+            Err(anyhow!(
+                "'{}' is not a valid {} name; `/` must separate non-empty parts",
+                name,
+                kind.noun()
+            )
+            .into())
+        } else {
+            Err(anyhow!(
+                "'{}' is not a valid {} name; it must be non-empty and free of '\"'",
+                name,
+                kind.noun()
+            )
+            .into())
+        }
+    }
 
-            // Create relationship
-            tx.execute(
-                "INSERT OR IGNORE INTO entry_tags (entry_id, tag_id) VALUES (?, ?)",
-                params![entry_id, tag_id],
-            )?;
+    fn entity_id(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        name: &str,
+    ) -> Result<Option<i64>, rusqlite::Error> {
+        tx.query_row(
+            &format!(
+                "SELECT id FROM {} WHERE entity_key(name) = entity_key(?) ORDER BY id LIMIT 1",
+                kind.table()
+            ),
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()
+    }
+
+    pub fn require_entity(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        name: &str,
+    ) -> Result<i64, Error> {
+        Core::entity_id(tx, kind, name)?
+            .ok_or_else(|| Error::EntityNotFound(format!("{}{}", kind.marker(), name)))
+    }
+
+    /// Replaces `old` markers with `new` in the entries linked to an entity.
+    /// The FTS index follows via the `entries_au` trigger.
+    fn rewrite_markers(
+        tx: &rusqlite::Transaction,
+        policy: &NormalizationPolicy,
+        kind: EntityKind,
+        entity_id: i64,
+        old: &str,
+        new: &str,
+    ) -> Result<usize, rusqlite::Error> {
+        let entries = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT e.id, e.content FROM entries e JOIN {} l ON l.entry_id = e.id
+                 WHERE l.{} = ?",
+                kind.link_table(),
+                kind.id_column()
+            ))?;
+            let rows = stmt.query_map(params![entity_id], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let old = policy.key(old);
+        let replacement = policy.format_marker(kind, new);
+        let mut rewritten = 0;
+        for (id, content) in entries {
+            let mut updated = String::new();
+            let mut last = 0;
+            for marker in policy.extract(&content) {
+                if marker.kind == kind && policy.key(&marker.name) == old {
+                    updated.push_str(&content[last..marker.start]);
+                    updated.push_str(&replacement);
+                    last = marker.end;
+                }
+            }
+            updated.push_str(&content[last..]);
+
+            if updated != content {
+                tx.execute(
+                    "UPDATE entries SET content = ? WHERE id = ?",
+                    params![updated, id],
+                )?;
+                rewritten += 1;
+            }
+        }
+
+        Ok(rewritten)
+    }
+
+    /// Deletes entities that no entry mentions, unless they carry metadata.
+    /// Projects and tags are also kept while something is nested below them.
+    fn remove_orphaned_entities(tx: &rusqlite::Transaction) -> Result<(), rusqlite::Error> {
+        for kind in EntityKind::ALL {
+            let (table, link_table, id_column) =
+                (kind.table(), kind.link_table(), kind.id_column());
+            let mut sql = format!(
+                "DELETE FROM {table} WHERE id NOT IN (SELECT {id_column} FROM {link_table})
+                 AND NOT archived
+                 AND id NOT IN (SELECT entity_id FROM entity_metadata WHERE kind = '{}')",
+                kind.noun()
+            );
+            if kind.is_hierarchical() {
+                sql.push_str(&format!(
+                    " AND id NOT IN (SELECT parent_id FROM {table} WHERE parent_id IS NOT NULL)"
+                ));
+            }
+            // Removing a leaf can orphan its parent, so repeat until stable.
+            while tx.execute(&sql, [])? > 0 {}
+        }
+        Ok(())
+    }
+
+    fn process_entities(
+        tx: &rusqlite::Transaction,
+        policy: &NormalizationPolicy,
+        entry_id: i64,
+        content: &str,
+    ) -> Result<(), rusqlite::Error> {
+        // Extract people (@name), projects (%name), and tags (#name)
+        for marker in policy.extract(content) {
+            let name = alias::resolve(tx, marker.kind, &marker.name)?;
+            Core::link_entity(tx, entry_id, marker.kind, &name)?;
         }
 
+        // Durations (~1h30m, [09:00-10:30])
+        tx.execute(
+            "UPDATE entries SET minutes = ? WHERE id = ?",
+            params![duration::minutes(content), entry_id],
+        )?;
+
         Ok(())
     }
+
+    /// Links an entry to the named entity, creating the entity if no existing
+    /// one has the same normalized name.
+    pub fn link_entity(
+        tx: &rusqlite::Transaction,
+        entry_id: i64,
+        kind: EntityKind,
+        name: &str,
+    ) -> Result<(), rusqlite::Error> {
+        let entity_id = Core::ensure_entity(tx, kind, name)?;
+
+        tx.execute(
+            &format!(
+                "INSERT OR IGNORE INTO {} (entry_id, {}) VALUES (?, ?)",
+                kind.link_table(),
+                kind.id_column()
+            ),
+            params![entry_id, entity_id],
+        )?;
+
+        Ok(())
+    }
+
+    /// The id of the named entity, created if needed. A new project or tag
+    /// with a `/` in its name is linked to its parent, which is created too.
+    pub fn ensure_entity(
+        tx: &rusqlite::Transaction,
+        kind: EntityKind,
+        name: &str,
+    ) -> Result<i64, rusqlite::Error> {
+        if let Some(id) = Core::entity_id(tx, kind, name)? {
+            return Ok(id);
+        }
+
+        match parent_name(name).filter(|_| kind.is_hierarchical()) {
+            Some(parent) => {
+                let parent_id = Core::ensure_entity(tx, kind, parent)?;
+                tx.execute(
+                    &format!(
+                        "INSERT INTO {} (name, parent_id) VALUES (?, ?)",
+                        kind.table()
+                    ),
+                    params![name, parent_id],
+                )?;
+            }
+            None => {
+                tx.execute(
+                    &format!("INSERT INTO {} (name) VALUES (?)", kind.table()),
+                    params![name],
+                )?;
+            }
+        }
+        Ok(tx.last_insert_rowid())
+    }
 }
@@ -0,0 +1,372 @@
+use crate::alias;
+use crate::core::{Core, Entry};
+use crate::entity::{EntityKind, EntryWithRelations};
+use crate::filter::EntryFilter;
+use crate::metadata::{self, Metadata};
+use crate::output::{self, join_list, CsvRecord, OutputFormat};
+use crate::stats::{bar, right_align, sparkline};
+use anyhow::anyhow;
+use chrono::{Datelike, Local, NaiveDate};
+use comfy_table::Table;
+use rusqlite::{params_from_iter, OptionalExtension};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashSet};
+
+/// Overview of one person, project or tag, as shown by `dia show person`,
+/// `project` and `tag`.
+#[derive(Debug, Serialize)]
+pub struct Dashboard {
+    pub kind: EntityKind,
+    pub name: String,
+    pub archived: bool,
+    pub metadata: BTreeMap<String, String>,
+    pub first_mention: Option<NaiveDate>,
+    pub last_mention: Option<NaiveDate>,
+    pub entries: usize,
+    pub active_days: usize,
+    /// Entries per month (`2025-03`), including quiet months
+    pub monthly: BTreeMap<String, i64>,
+    pub with_people: Vec<Mentions>,
+    pub with_projects: Vec<Mentions>,
+    pub with_tags: Vec<Mentions>,
+    pub latest: Vec<EntryWithRelations>,
+}
+
+/// How many entries mention an entity.
+#[derive(Debug, Serialize)]
+pub struct Mentions {
+    pub name: String,
+    pub entries: i64,
+}
+
+impl Dashboard {
+    fn with(&self, kind: EntityKind) -> &[Mentions] {
+        match kind {
+            EntityKind::Person => &self.with_people,
+            EntityKind::Project => &self.with_projects,
+            EntityKind::Tag => &self.with_tags,
+        }
+    }
+}
+
+impl CsvRecord for Dashboard {
+    const HEADERS: &'static [&'static str] = &[
+        "kind",
+        "name",
+        "archived",
+        "metadata",
+        "first_mention",
+        "last_mention",
+        "entries",
+        "active_days",
+        "monthly",
+        "with_people",
+        "with_projects",
+        "with_tags",
+        "latest",
+    ];
+
+    fn csv_row(&self) -> Vec<String> {
+        let pairs = |pairs: Vec<String>| join_list(&pairs);
+        let mentions = |kind| {
+            pairs(
+                self.with(kind)
+                    .iter()
+                    .map(|m| format!("{}={}", m.name, m.entries))
+                    .collect(),
+            )
+        };
+        vec![
+            self.kind.noun().to_string(),
+            self.name.clone(),
+            self.archived.to_string(),
+            pairs(
+                self.metadata
+                    .iter()
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect(),
+            ),
+            self.first_mention
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+            self.last_mention.map(|d| d.to_string()).unwrap_or_default(),
+            self.entries.to_string(),
+            self.active_days.to_string(),
+            pairs(
+                self.monthly
+                    .iter()
+                    .map(|(month, count)| format!("{month}={count}"))
+                    .collect(),
+            ),
+            mentions(EntityKind::Person),
+            mentions(EntityKind::Project),
+            mentions(EntityKind::Tag),
+            pairs(
+                self.latest
+                    .iter()
+                    .map(|entry| entry.id.to_string())
+                    .collect(),
+            ),
+        ]
+    }
+}
+
+/// Prints an overview of one person, project or tag: when it was first and
+/// last mentioned, monthly activity, the entities it appears with most and
+/// its latest entries. A project or tag includes everything nested below it.
+pub fn show_dashboard(
+    core: &Core,
+    kind: EntityKind,
+    name: &str,
+    latest: usize,
+    top: usize,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let dashboard = load(core, kind, name, latest, top)?;
+    if format != OutputFormat::Table {
+        return output::write_records(&[dashboard], format);
+    }
+
+    let title = core.policy.format_marker(kind, &dashboard.name);
+    let details = Metadata {
+        archived: dashboard.archived,
+        fields: dashboard.metadata.clone(),
+    }
+    .summary();
+    if details.is_empty() {
+        println!("{}", title);
+    } else {
+        println!("{} ({})", title, details);
+    }
+    println!();
+
+    let (Some(first), Some(last)) = (dashboard.first_mention, dashboard.last_mention) else {
+        println!("No entries mention {}.", title);
+        return Ok(());
+    };
+
+    print_summary(&dashboard, first, last);
+    print_monthly_activity(&dashboard.monthly);
+
+    for other in EntityKind::ALL {
+        let counts = dashboard.with(other);
+        if counts.is_empty() {
+            continue;
+        }
+        let mut table = Table::new();
+        table.set_header(vec![
+            format!("With {}", other.table()),
+            "Entries".to_string(),
+        ]);
+        for mentions in counts {
+            table.add_row(vec![
+                core.policy.format_marker(other, &mentions.name),
+                mentions.entries.to_string(),
+            ]);
+        }
+        right_align(&mut table, 1);
+        println!("{table}");
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec!["ID", "Date", "Latest entries"]);
+    for entry in &dashboard.latest {
+        table.add_row(vec![
+            entry.id.to_string(),
+            entry.date.clone(),
+            entry.content.clone(),
+        ]);
+    }
+    println!("{table}");
+
+    Ok(())
+}
+
+/// Gathers the dashboard of the entity `name`, which may carry its marker or
+/// be an alias.
+fn load(
+    core: &Core,
+    kind: EntityKind,
+    name: &str,
+    latest: usize,
+    top: usize,
+) -> anyhow::Result<Dashboard> {
+    let name = name.strip_prefix(kind.marker()).unwrap_or(name);
+    let name = name
+        .strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(name);
+    let name = alias::resolve(&core.conn, kind, name)?;
+    let name: String = core
+        .conn
+        .query_row(
+            &format!(
+                "SELECT name FROM {} WHERE entity_key(name) = entity_key(?) ORDER BY id LIMIT 1",
+                kind.table()
+            ),
+            [&name],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| anyhow!("{} not found", core.policy.format_marker(kind, &name)))?;
+
+    let mut filter = EntryFilter::default();
+    match kind {
+        EntityKind::Person => filter.person = Some(name.clone()),
+        EntityKind::Project => filter.project = Some(name.clone()),
+        EntityKind::Tag => filter.tag = Some(name.clone()),
+    }
+    let entries = filter.entries(&core.conn)?;
+
+    let mentions = |other| -> rusqlite::Result<Vec<Mentions>> {
+        Ok(co_occurring(core, &filter, kind, &name, other, top)?
+            .into_iter()
+            .map(|(name, entries)| Mentions { name, entries })
+            .collect())
+    };
+    let with_people = mentions(EntityKind::Person)?;
+    let with_projects = mentions(EntityKind::Project)?;
+    let with_tags = mentions(EntityKind::Tag)?;
+
+    let details = metadata::load(&core.conn, kind)?
+        .remove(&name)
+        .unwrap_or_default();
+    let first_mention = entries.first().map(|entry| entry.date);
+    let last_mention = entries.last().map(|entry| entry.date);
+    let active_days = entries
+        .iter()
+        .map(|entry| entry.date)
+        .collect::<HashSet<_>>()
+        .len();
+    let monthly = monthly_counts(&entries);
+    let count = entries.len();
+    let latest_entries = entries.into_iter().rev().take(latest).collect();
+
+    Ok(Dashboard {
+        kind,
+        name,
+        archived: details.archived,
+        metadata: details.fields,
+        first_mention,
+        last_mention,
+        entries: count,
+        active_days,
+        monthly,
+        with_people,
+        with_projects,
+        with_tags,
+        latest: EntryWithRelations::from_entries(&core.conn, latest_entries)?,
+    })
+}
+
+fn print_summary(dashboard: &Dashboard, first: NaiveDate, last: NaiveDate) {
+    let today = Local::now().date_naive();
+
+    let mut table = Table::new();
+    table.set_header(vec!["Summary", ""]);
+    table.add_row(vec!["First mention".to_string(), first.to_string()]);
+    table.add_row(vec![
+        "Last mention".to_string(),
+        match (today - last).num_days() {
+            0 => format!("{} (today)", last),
+            1 => format!("{} (yesterday)", last),
+            days if days > 0 => format!("{} ({} days ago)", last, days),
+            _ => last.to_string(),
+        },
+    ]);
+    table.add_row(vec!["Entries".to_string(), dashboard.entries.to_string()]);
+    table.add_row(vec![
+        "Active days".to_string(),
+        dashboard.active_days.to_string(),
+    ]);
+    println!("{table}");
+}
+
+/// Entries per month from the first mention to the last, including quiet
+/// months.
+fn monthly_counts(entries: &[Entry]) -> BTreeMap<String, i64> {
+    let (Some(first), Some(last)) = (entries.first(), entries.last()) else {
+        return BTreeMap::new();
+    };
+    let mut months = BTreeMap::new();
+    let (mut year, mut month) = (first.date.year(), first.date.month());
+    while (year, month) <= (last.date.year(), last.date.month()) {
+        months.insert((year, month), 0);
+        (year, month) = if month == 12 {
+            (year + 1, 1)
+        } else {
+            (year, month + 1)
+        };
+    }
+    for entry in entries {
+        *months
+            .entry((entry.date.year(), entry.date.month()))
+            .or_insert(0) += 1;
+    }
+    months
+        .into_iter()
+        .map(|((year, month), count)| (format!("{year}-{month:02}"), count))
+        .collect()
+}
+
+fn print_monthly_activity(months: &BTreeMap<String, i64>) {
+    let counts = months.values().copied().collect::<Vec<i64>>();
+    let max = counts.iter().copied().max().unwrap_or(0);
+    let mut table = Table::new();
+    table.set_header(vec!["Month", "Entries", ""]);
+    for (month, count) in months {
+        table.add_row(vec![month.clone(), count.to_string(), bar(*count, max)]);
+    }
+    right_align(&mut table, 1);
+    println!("{table}");
+    println!("{}", sparkline(&counts));
+}
+
+/// The entities of kind `other` mentioned most often in the entries matching
+/// `filter`, leaving out the entity (`kind`, `name`) itself and anything
+/// nested below it.
+fn co_occurring(
+    core: &Core,
+    filter: &EntryFilter,
+    kind: EntityKind,
+    name: &str,
+    other: EntityKind,
+    top: usize,
+) -> rusqlite::Result<Vec<(String, i64)>> {
+    let (conditions, mut params) = filter.conditions(&core.conn)?;
+    let mut query = format!(
+        "SELECT x.name, COUNT(*) FROM {link_table} l
+         JOIN {table} x ON x.id = l.{id_column}
+         JOIN entries e ON e.id = l.entry_id
+         WHERE {conditions}",
+        link_table = other.link_table(),
+        table = other.table(),
+        id_column = other.id_column(),
+        conditions = conditions.join(" AND ")
+    );
+    if other == kind && kind.is_hierarchical() {
+        // The entries come from the whole subtree, so leave all of it out.
+        query.push_str(&format!(
+            " AND x.id NOT IN (
+                 WITH RECURSIVE tree(id) AS (
+                     SELECT id FROM {table} WHERE entity_key(name) = entity_key(?)
+                     UNION SELECT c.id FROM {table} c JOIN tree ON c.parent_id = tree.id
+                 )
+                 SELECT id FROM tree)",
+            table = kind.table()
+        ));
+        params.push(name.to_string());
+    } else if other == kind {
+        query.push_str(" AND entity_key(x.name) != entity_key(?)");
+        params.push(name.to_string());
+    }
+    query.push_str(&format!(
+        " GROUP BY x.id ORDER BY COUNT(*) DESC, x.name LIMIT {top}"
+    ));
+
+    let mut stmt = core.conn.prepare(&query)?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    rows.collect()
+}
@@ -0,0 +1,282 @@
+use anyhow::{anyhow, Result};
+use chrono::{Datelike, Days, Duration, Months, NaiveDate, Weekday};
+
+/// An inclusive range of days.
+pub type DateSpan = (NaiveDate, NaiveDate);
+
+/// Parses a date expression into the span of days it covers, relative to
+/// `today`. Understands ISO dates (`2025-03-14`), months (`2025-03`,
+/// `march 2025`), years, quarters (`Q2 2025`), ISO weeks (`2025-W14`),
+/// relative days (`yesterday`, `last friday`, `3 days ago`), relative periods
+/// (`this month`, `last week`, `last 7 days`) and ranges joined by `..` or
+/// `to`.
+pub fn parse_span(expr: &str, today: NaiveDate) -> Result<DateSpan> {
+    let expr = expr.trim().to_lowercase();
+    if expr.is_empty() {
+        return Err(anyhow!("Empty date expression"));
+    }
+
+    if let Some((from, to)) = split_range(&expr) {
+        let (start, _) = parse_single(from.trim(), today)?;
+        let (_, end) = parse_single(to.trim(), today)?;
+        if start > end {
+            return Err(anyhow!("Invalid date range: {} is after {}", start, end));
+        }
+        return Ok((start, end));
+    }
+
+    parse_single(&expr, today)
+}
+
+/// Parses a date expression that must name exactly one day, such as the
+/// `--date` of a new entry.
+pub fn parse_day(expr: &str, today: NaiveDate) -> Result<NaiveDate> {
+    match parse_span(expr, today)? {
+        (from, to) if from == to => Ok(from),
+        (from, to) => Err(anyhow!(
+            "'{}' covers {} to {}; expected a single day",
+            expr.trim(),
+            from,
+            to
+        )),
+    }
+}
+
+fn split_range(expr: &str) -> Option<(&str, &str)> {
+    expr.split_once("..").or_else(|| {
+        expr.strip_prefix("from ")
+            .unwrap_or(expr)
+            .split_once(" to ")
+    })
+}
+
+fn parse_single(expr: &str, today: NaiveDate) -> Result<DateSpan> {
+    let words = expr.split_whitespace().collect::<Vec<_>>();
+    let day = |date: NaiveDate| Ok((date, date));
+
+    match words.as_slice() {
+        ["today"] => day(today),
+        ["yesterday"] => day(today - Duration::days(1)),
+        ["tomorrow"] => day(today + Duration::days(1)),
+        [count, unit, "ago"] => day(shift_back(today, parse_count(count)?, unit)?),
+        ["last" | "past", count, unit] => {
+            let count = parse_count(count)?;
+            if count == 0 {
+                return Err(anyhow!("'{}' covers no days", expr));
+            }
+            Ok((shift_back(today, count, unit)? + Duration::days(1), today))
+        }
+        [relative @ ("this" | "last" | "next"), unit] => {
+            let offset = match *relative {
+                "last" => -1,
+                "next" => 1,
+                _ => 0,
+            };
+            match parse_weekday(unit) {
+                Some(weekday) if offset == 0 => {
+                    let (monday, _) = relative_period(today, "week", 0)?;
+                    day(monday + Duration::days(weekday.num_days_from_monday().into()))
+                }
+                Some(weekday) => day(relative_weekday(today, weekday, offset)),
+                None => relative_period(today, unit, offset),
+            }
+        }
+        [word] => {
+            if let Some(weekday) = parse_weekday(word) {
+                return day(relative_weekday(today, weekday, 0));
+            }
+            if let Some(month) = parse_month_name(word) {
+                return month_span(today.year(), month);
+            }
+            if let Some(quarter) = parse_quarter(word) {
+                return quarter_span(today.year(), quarter);
+            }
+            parse_numeric(word)
+        }
+        [first, second] => {
+            if let (Some(quarter), Ok(year)) = (parse_quarter(first), second.parse()) {
+                return quarter_span(year, quarter);
+            }
+            if let (Ok(year), Some(quarter)) = (first.parse(), parse_quarter(second)) {
+                return quarter_span(year, quarter);
+            }
+            if let (Some(month), Ok(year)) = (parse_month_name(first), second.parse()) {
+                return month_span(year, month);
+            }
+            Err(unrecognised(expr))
+        }
+        _ => Err(unrecognised(expr)),
+    }
+}
+
+/// Absolute forms: `YYYY-MM-DD`, `YYYY-MM`, `YYYY`, `YYYY-Www` and `YYYY-Qn`.
+fn parse_numeric(word: &str) -> Result<DateSpan> {
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Ok((date, date));
+    }
+
+    if let Some((year, rest)) = word.split_once('-') {
+        let year: i32 = year.parse().map_err(|_| unrecognised(word))?;
+        if let Some(week) = rest.strip_prefix('w') {
+            let week: u32 = week.parse().map_err(|_| unrecognised(word))?;
+            let monday = NaiveDate::from_isoywd_opt(year, week, Weekday::Mon)
+                .ok_or_else(|| anyhow!("{} has no ISO week {}", year, week))?;
+            return Ok((monday, monday + Duration::days(6)));
+        }
+        if let Some(quarter) = parse_quarter(rest) {
+            return quarter_span(year, quarter);
+        }
+        if let Ok(month) = rest.parse() {
+            return month_span(year, month);
+        }
+        return Err(unrecognised(word));
+    }
+
+    if word.len() == 4 {
+        if let Ok(year) = word.parse() {
+            return year_span(year);
+        }
+    }
+
+    Err(unrecognised(word))
+}
+
+fn unrecognised(expr: &str) -> anyhow::Error {
+    anyhow!(
+        "Unrecognised date '{}' (try YYYY-MM-DD, 'yesterday', 'last friday', \
+         '3 days ago', 'this month', 'Q2 2025' or '2025-W14')",
+        expr
+    )
+}
+
+fn parse_count(word: &str) -> Result<u32> {
+    match word {
+        "a" | "an" | "one" => Ok(1),
+        _ => word
+            .parse()
+            .map_err(|_| anyhow!("Expected a number, found '{}'", word)),
+    }
+}
+
+fn shift_back(today: NaiveDate, count: u32, unit: &str) -> Result<NaiveDate> {
+    let shifted = match unit.trim_end_matches('s') {
+        "day" => today.checked_sub_days(Days::new(count.into())),
+        "week" => today.checked_sub_days(Days::new(u64::from(count) * 7)),
+        "month" => today.checked_sub_months(Months::new(count)),
+        "year" => count
+            .checked_mul(12)
+            .and_then(|months| today.checked_sub_months(Months::new(months))),
+        _ => return Err(anyhow!("Unknown unit '{}'", unit)),
+    };
+    shifted.ok_or_else(|| anyhow!("Date out of range"))
+}
+
+fn relative_period(today: NaiveDate, unit: &str, offset: i32) -> Result<DateSpan> {
+    match unit {
+        "week" => {
+            let monday = today - Duration::days(today.weekday().num_days_from_monday().into())
+                + Duration::weeks(offset.into());
+            Ok((monday, monday + Duration::days(6)))
+        }
+        "month" => {
+            let first = shift_months(first_of_month(today), offset)?;
+            month_span(first.year(), first.month())
+        }
+        "quarter" => {
+            let index = today.year() * 4 + (today.month0() / 3) as i32 + offset;
+            quarter_span(index.div_euclid(4), index.rem_euclid(4) as u32 + 1)
+        }
+        "year" => year_span(today.year() + offset),
+        _ => Err(anyhow!("Unknown period '{}'", unit)),
+    }
+}
+
+/// `offset` 0 is the most recent such weekday (today included), -1 the one
+/// strictly before today and 1 the one strictly after.
+fn relative_weekday(today: NaiveDate, weekday: Weekday, offset: i32) -> NaiveDate {
+    let back = (7 + today.weekday().num_days_from_monday() - weekday.num_days_from_monday()) % 7;
+    match offset {
+        0 => today - Duration::days(back.into()),
+        o if o < 0 => today - Duration::days(if back == 0 { 7 } else { back.into() }),
+        _ => {
+            let ahead = (7 - back) % 7;
+            today + Duration::days(if ahead == 0 { 7 } else { ahead.into() })
+        }
+    }
+}
+
+fn parse_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" | "mon" => Some(Weekday::Mon),
+        "tuesday" | "tue" | "tues" => Some(Weekday::Tue),
+        "wednesday" | "wed" => Some(Weekday::Wed),
+        "thursday" | "thu" | "thurs" => Some(Weekday::Thu),
+        "friday" | "fri" => Some(Weekday::Fri),
+        "saturday" | "sat" => Some(Weekday::Sat),
+        "sunday" | "sun" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_month_name(word: &str) -> Option<u32> {
+    const MONTHS: [&str; 12] = [
+        "january",
+        "february",
+        "march",
+        "april",
+        "may",
+        "june",
+        "july",
+        "august",
+        "september",
+        "october",
+        "november",
+        "december",
+    ];
+    if word.len() < 3 {
+        return None;
+    }
+    MONTHS
+        .iter()
+        .position(|month| month.starts_with(word))
+        .map(|index| index as u32 + 1)
+}
+
+fn parse_quarter(word: &str) -> Option<u32> {
+    word.strip_prefix('q')
+        .and_then(|q| q.parse().ok())
+        .filter(|q| (1..=4).contains(q))
+}
+
+fn first_of_month(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+fn shift_months(date: NaiveDate, offset: i32) -> Result<NaiveDate> {
+    let months = Months::new(offset.unsigned_abs());
+    if offset < 0 {
+        date.checked_sub_months(months)
+    } else {
+        date.checked_add_months(months)
+    }
+    .ok_or_else(|| anyhow!("Date out of range"))
+}
+
+fn month_span(year: i32, month: u32) -> Result<DateSpan> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)
+        .ok_or_else(|| anyhow!("Invalid month {}-{:02}", year, month))?;
+    let last = shift_months(first, 1)? - Duration::days(1);
+    Ok((first, last))
+}
+
+fn quarter_span(year: i32, quarter: u32) -> Result<DateSpan> {
+    let (first, _) = month_span(year, (quarter - 1) * 3 + 1)?;
+    let (_, last) = month_span(year, quarter * 3)?;
+    Ok((first, last))
+}
+
+fn year_span(year: i32) -> Result<DateSpan> {
+    let (first, _) = month_span(year, 1)?;
+    let (_, last) = month_span(year, 12)?;
+    Ok((first, last))
+}
@@ -1,125 +1,476 @@
 // src/db.rs
-use rusqlite::{Connection, Result as SqliteResult};
-use std::path::PathBuf;
-use dirs::home_dir;
-
-pub fn get_db_path() -> PathBuf {
-    let config_dir = home_dir()
-        .expect("Could not find home directory")
-        .join(".config/dia");
-    
-    config_dir.join("diary.db")
-}
-
-pub fn init_db(conn: &Connection) -> SqliteResult<()> {
-    // Create entries table
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS entries (
+use crate::entity::parent_name;
+use anyhow::anyhow;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult, Transaction};
+
+/// A single schema migration. Migrations run in order, each inside its own
+/// transaction, and the database's `PRAGMA user_version` records how many
+/// have been applied.
+type Migration = fn(&Transaction) -> SqliteResult<()>;
+
+const MIGRATIONS: &[Migration] = &[
+    unify_legacy_schemas,
+    add_review_state,
+    add_aliases,
+    add_entity_parents,
+    add_entity_metadata,
+    add_entry_minutes,
+    add_timer,
+    add_alias_keys,
+];
+
+/// Brings the database up to the latest schema version.
+pub fn migrate(conn: &mut Connection) -> anyhow::Result<()> {
+    let version = user_version(conn)?;
+    if version > MIGRATIONS.len() {
+        return Err(anyhow!(
+            "Database schema version {} is newer than this version of dia supports ({})",
+            version,
+            MIGRATIONS.len()
+        ));
+    }
+
+    if version == MIGRATIONS.len() {
+        return Ok(());
+    }
+
+    // Table rebuilds need foreign key enforcement off; the pragma is a no-op
+    // inside a transaction, so it is toggled around the whole run.
+    conn.execute_batch("PRAGMA foreign_keys = OFF;")?;
+    let result = apply_pending(conn, version);
+    conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+    result
+}
+
+fn apply_pending(conn: &mut Connection, from: usize) -> anyhow::Result<()> {
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(from) {
+        let tx = conn.transaction()?;
+        migration(&tx)?;
+
+        let violations: i64 =
+            tx.query_row("SELECT COUNT(*) FROM pragma_foreign_key_check", [], |row| {
+                row.get(0)
+            })?;
+        if violations > 0 {
+            return Err(anyhow!(
+                "Migration {} left {} foreign key violations",
+                index + 1,
+                violations
+            ));
+        }
+
+        tx.pragma_update(None, "user_version", index + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+pub fn user_version(conn: &Connection) -> SqliteResult<usize> {
+    conn.query_row("PRAGMA user_version", [], |row| row.get(0))
+}
+
+fn table_exists(tx: &Transaction, table: &str) -> SqliteResult<bool> {
+    tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?)",
+        [table],
+        |row| row.get(0),
+    )
+}
+
+fn has_column(tx: &Transaction, table: &str, column: &str) -> SqliteResult<bool> {
+    tx.query_row(
+        "SELECT EXISTS (SELECT 1 FROM pragma_table_info(?) WHERE name = ?)",
+        [table, column],
+        |row| row.get(0),
+    )
+}
+
+/// Version 1: merges the two historical schemas (the one created by
+/// `Core::init_tables` and the one created by `db::init_db`) into a single
+/// layout. Existing tables are renamed aside, recreated, and their rows
+/// copied across, so diaries created by either schema keep their data.
+fn unify_legacy_schemas(tx: &Transaction) -> SqliteResult<()> {
+    const TABLES: [&str; 7] = [
+        "entries",
+        "people",
+        "projects",
+        "tags",
+        "entry_people",
+        "entry_projects",
+        "entry_tags",
+    ];
+
+    tx.execute_batch(
+        "DROP TRIGGER IF EXISTS entries_ai;
+         DROP TRIGGER IF EXISTS entries_ad;
+         DROP TRIGGER IF EXISTS entries_au;
+         DROP TABLE IF EXISTS entries_fts;
+         DROP INDEX IF EXISTS idx_entries_date;
+         DROP INDEX IF EXISTS idx_projects_name;
+         DROP INDEX IF EXISTS idx_tags_name;
+         DROP INDEX IF EXISTS idx_people_name;",
+    )?;
+
+    let mut legacy = Vec::new();
+    for table in TABLES {
+        if table_exists(tx, table)? {
+            tx.execute_batch(&format!("ALTER TABLE {table} RENAME TO legacy_{table}"))?;
+            legacy.push(table);
+        }
+    }
+
+    tx.execute_batch(
+        r#"
+        CREATE TABLE entries (
             id INTEGER PRIMARY KEY,
-            date TEXT NOT NULL,
             content TEXT NOT NULL,
+            date DATE NOT NULL,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
             last_reviewed TEXT
-        )",
-        [],
-    )?;
-    
-    // Create entity tables
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS projects (
+        );
+
+        CREATE TABLE people (
             id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL UNIQUE
-        )",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tags (
+            name TEXT NOT NULL UNIQUE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE projects (
             id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL UNIQUE
-        )",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS people (
+            name TEXT NOT NULL UNIQUE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE tags (
             id INTEGER PRIMARY KEY,
-            name TEXT NOT NULL UNIQUE
-        )",
-        [],
-    )?;
-    
-    // Create relation tables
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS entry_projects (
-            entry_id INTEGER,
-            project_id INTEGER,
-            PRIMARY KEY (entry_id, project_id),
-            FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
-            FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS entry_tags (
-            entry_id INTEGER,
-            tag_id INTEGER,
-            PRIMARY KEY (entry_id, tag_id),
-            FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
-            FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS entry_people (
-            entry_id INTEGER,
-            person_id INTEGER,
-            PRIMARY KEY (entry_id, person_id),
-            FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
-            FOREIGN KEY (person_id) REFERENCES people (id) ON DELETE CASCADE
-        )",
-        [],
-    )?;
-    
-    // Create FTS virtual table for full-text search
-    conn.execute(
-        "CREATE VIRTUAL TABLE IF NOT EXISTS entries_fts USING fts5(
+            name TEXT NOT NULL UNIQUE,
+            created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+        );
+
+        CREATE TABLE entry_people (
+            entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            person_id INTEGER NOT NULL REFERENCES people(id) ON DELETE CASCADE,
+            PRIMARY KEY (entry_id, person_id)
+        );
+
+        CREATE TABLE entry_projects (
+            entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            project_id INTEGER NOT NULL REFERENCES projects(id) ON DELETE CASCADE,
+            PRIMARY KEY (entry_id, project_id)
+        );
+
+        CREATE TABLE entry_tags (
+            entry_id INTEGER NOT NULL REFERENCES entries(id) ON DELETE CASCADE,
+            tag_id INTEGER NOT NULL REFERENCES tags(id) ON DELETE CASCADE,
+            PRIMARY KEY (entry_id, tag_id)
+        );
+
+        CREATE INDEX idx_entries_date ON entries (date);
+        CREATE INDEX idx_entry_people_person ON entry_people (person_id);
+        CREATE INDEX idx_entry_projects_project ON entry_projects (project_id);
+        CREATE INDEX idx_entry_tags_tag ON entry_tags (tag_id);
+
+        CREATE VIRTUAL TABLE entries_fts USING fts5(
             content,
-            content='entries',
-            content_rowid='id'
-        )",
-        [],
-    )?;
-    
-    // Create trigger to keep FTS table updated
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS entries_ai AFTER INSERT ON entries BEGIN
+            content = 'entries',
+            content_rowid = 'id',
+            tokenize = 'porter unicode61'
+        );
+
+        CREATE TRIGGER entries_ai AFTER INSERT ON entries BEGIN
             INSERT INTO entries_fts(rowid, content) VALUES (new.id, new.content);
-        END",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS entries_ad AFTER DELETE ON entries BEGIN
-            INSERT INTO entries_fts(entries_fts, rowid, content) VALUES('delete', old.id, old.content);
-        END",
-        [],
-    )?;
-    
-    conn.execute(
-        "CREATE TRIGGER IF NOT EXISTS entries_au AFTER UPDATE ON entries BEGIN
-            INSERT INTO entries_fts(entries_fts, rowid, content) VALUES('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER entries_ad AFTER DELETE ON entries BEGIN
+            INSERT INTO entries_fts(entries_fts, rowid, content) VALUES ('delete', old.id, old.content);
+        END;
+
+        CREATE TRIGGER entries_au AFTER UPDATE OF content ON entries BEGIN
+            INSERT INTO entries_fts(entries_fts, rowid, content) VALUES ('delete', old.id, old.content);
             INSERT INTO entries_fts(rowid, content) VALUES (new.id, new.content);
-        END",
-        [],
+        END;
+        "#,
+    )?;
+
+    for table in legacy {
+        copy_legacy_rows(tx, table)?;
+        tx.execute_batch(&format!("DROP TABLE legacy_{table}"))?;
+    }
+
+    tx.execute_batch("INSERT INTO entries_fts(entries_fts) VALUES ('rebuild');")
+}
+
+/// Version 2: SM-2 scheduling state for `dia review`. Entries without a row
+/// have never been reviewed and are due immediately.
+fn add_review_state(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE review_state (
+            entry_id INTEGER PRIMARY KEY REFERENCES entries(id) ON DELETE CASCADE,
+            ease REAL NOT NULL DEFAULT 2.5,
+            interval_days INTEGER NOT NULL DEFAULT 0,
+            repetitions INTEGER NOT NULL DEFAULT 0,
+            due_date DATE NOT NULL
+        );
+
+        CREATE INDEX idx_review_state_due ON review_state (due_date);
+        "#,
+    )
+}
+
+/// Version 3: alternative names that resolve to a canonical person, project
+/// or tag when entries are logged. Aliases match case-insensitively.
+fn add_aliases(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE aliases (
+            id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL CHECK (kind IN ('person', 'project', 'tag')),
+            alias TEXT NOT NULL COLLATE NOCASE,
+            name TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (kind, alias)
+        );
+        "#,
+    )
+}
+
+/// Version 4: parent links for projects and tags, so that `/`-separated
+/// names such as `client/website` form a tree. Missing parents of existing
+/// nested names are created.
+fn add_entity_parents(tx: &Transaction) -> SqliteResult<()> {
+    for table in ["projects", "tags"] {
+        tx.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN parent_id INTEGER
+                 REFERENCES {table}(id) ON DELETE SET NULL;
+             CREATE INDEX idx_{table}_parent ON {table} (parent_id);"
+        ))?;
+
+        let nested = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT id, name FROM {table} WHERE name LIKE '%/%'"
+            ))?;
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?;
+            rows.collect::<SqliteResult<Vec<_>>>()?
+        };
+        for (id, name) in nested {
+            if let Some(parent) = parent_name(&name) {
+                let parent_id = entity_id_by_name(tx, table, parent)?;
+                tx.execute(
+                    &format!("UPDATE {table} SET parent_id = ? WHERE id = ?"),
+                    params![parent_id, id],
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Version 5: an `archived` flag on every entity, and free-form `key = value`
+/// details (status, description, ...) in `entity_metadata`. Details follow
+/// their entity when it is deleted.
+fn add_entity_metadata(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE entity_metadata (
+            kind TEXT NOT NULL CHECK (kind IN ('person', 'project', 'tag')),
+            entity_id INTEGER NOT NULL,
+            key TEXT NOT NULL,
+            value TEXT NOT NULL,
+            PRIMARY KEY (kind, entity_id, key)
+        );
+        "#,
     )?;
-    
-    // Create indices
-    conn.execute("CREATE INDEX IF NOT EXISTS idx_entries_date ON entries (date)", [])?;
-    conn.execute("CREATE INDEX IF NOT EXISTS idx_projects_name ON projects (name)", [])?;
-    conn.execute("CREATE INDEX IF NOT EXISTS idx_tags_name ON tags (name)", [])?;
-    conn.execute("CREATE INDEX IF NOT EXISTS idx_people_name ON people (name)", [])?;
-    
+
+    for (table, kind) in [
+        ("people", "person"),
+        ("projects", "project"),
+        ("tags", "tag"),
+    ] {
+        tx.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN archived INTEGER NOT NULL DEFAULT 0;
+             CREATE TRIGGER {table}_metadata_ad AFTER DELETE ON {table} BEGIN
+                 DELETE FROM entity_metadata WHERE kind = '{kind}' AND entity_id = old.id;
+             END;"
+        ))?;
+    }
+    Ok(())
+}
+
+/// Version 6: minutes spent per entry, from duration markers such as `~1h30m`
+/// or `[09:00-10:30]` in its text.
+fn add_entry_minutes(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch("ALTER TABLE entries ADD COLUMN minutes INTEGER;")?;
+
+    let entries = {
+        let mut stmt = tx.prepare("SELECT id, content FROM entries")?;
+        let rows = stmt.query_map([], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })?;
+        rows.collect::<SqliteResult<Vec<_>>>()?
+    };
+    for (id, content) in entries {
+        if let Some(minutes) = v6_minutes(&content) {
+            tx.execute(
+                "UPDATE entries SET minutes = ? WHERE id = ?",
+                params![minutes, id],
+            )?;
+        }
+    }
     Ok(())
 }
+
+/// The duration parser as of version 6, frozen so the backfill gives the same
+/// result however `duration::minutes` changes later.
+fn v6_minutes(text: &str) -> Option<i64> {
+    let pattern = Regex::new(
+        r"(?:^|[\s(,;])~(?:(\d+(?:\.\d+)?)h)?(?:(\d+)m)?\b|\[(\d{1,2}):(\d{2}) ?[-–] ?(\d{1,2}):(\d{2})\]",
+    )
+    .unwrap();
+    let mut total = None;
+    for caps in pattern.captures_iter(text) {
+        let minutes = if caps.get(3).is_some() {
+            let time = |hour: usize, minute: usize| -> Option<i64> {
+                let (hour, minute) = (
+                    caps[hour].parse::<i64>().ok()?,
+                    caps[minute].parse::<i64>().ok()?,
+                );
+                (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+            };
+            match (time(3, 4), time(5, 6)) {
+                (Some(start), Some(end)) => (end - start).rem_euclid(24 * 60),
+                _ => continue,
+            }
+        } else {
+            let hours = caps.get(1).map(|h| h.as_str().parse::<f64>());
+            let minutes = caps.get(2).map(|m| m.as_str().parse::<i64>());
+            if hours.is_none() && minutes.is_none() {
+                continue;
+            }
+            // Durations too long to count in minutes are not durations.
+            let from_hours = match hours {
+                Some(Ok(hours)) if hours * 60.0 < i64::MAX as f64 => (hours * 60.0).round() as i64,
+                Some(_) => continue,
+                None => 0,
+            };
+            let minutes = minutes.unwrap_or(Ok(0)).ok();
+            match minutes.and_then(|minutes| from_hours.checked_add(minutes)) {
+                Some(minutes) => minutes,
+                None => continue,
+            }
+        };
+        if let Some(sum) = total.unwrap_or(0_i64).checked_add(minutes) {
+            total = Some(sum);
+        }
+    }
+    total
+}
+
+/// Version 7: the timer of `dia start`. The table holds at most one row, so
+/// only one timer can run at a time.
+fn add_timer(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE timer (
+            id INTEGER PRIMARY KEY CHECK (id = 1),
+            description TEXT NOT NULL,
+            started_at DATETIME NOT NULL,
+            paused_at DATETIME,
+            paused_seconds INTEGER NOT NULL DEFAULT 0
+        );
+        "#,
+    )
+}
+
+/// Version 8: aliases are compared through `entity_key`, like entity names,
+/// so whether case matters follows the normalization policy rather than a
+/// fixed `NOCASE` collation.
+fn add_alias_keys(tx: &Transaction) -> SqliteResult<()> {
+    tx.execute_batch(
+        r#"
+        CREATE TABLE aliases_v8 (
+            id INTEGER PRIMARY KEY,
+            kind TEXT NOT NULL CHECK (kind IN ('person', 'project', 'tag')),
+            alias TEXT NOT NULL,
+            name TEXT NOT NULL,
+            created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+            UNIQUE (kind, alias)
+        );
+        INSERT INTO aliases_v8 (id, kind, alias, name, created_at)
+            SELECT id, kind, alias, name, created_at FROM aliases;
+        DROP TABLE aliases;
+        ALTER TABLE aliases_v8 RENAME TO aliases;
+        "#,
+    )
+}
+
+/// Looks up an entity by exact name, creating it and its parents if needed.
+/// Migrations run before `entity_key` is registered, so this cannot use the
+/// normalized lookup in `Core`.
+fn entity_id_by_name(tx: &Transaction, table: &str, name: &str) -> SqliteResult<i64> {
+    let existing = tx
+        .query_row(
+            &format!("SELECT id FROM {table} WHERE name = ? ORDER BY id LIMIT 1"),
+            params![name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let parent_id = parent_name(name)
+        .map(|parent| entity_id_by_name(tx, table, parent))
+        .transpose()?;
+    tx.execute(
+        &format!("INSERT INTO {table} (name, parent_id) VALUES (?, ?)"),
+        params![name, parent_id],
+    )?;
+    Ok(tx.last_insert_rowid())
+}
+
+/// Copies rows from `legacy_<table>` into `<table>`, filling columns that the
+/// older schema lacked and skipping link rows that point at missing records.
+fn copy_legacy_rows(tx: &Transaction, table: &str) -> SqliteResult<()> {
+    let legacy = format!("legacy_{table}");
+    let created_at = if has_column(tx, &legacy, "created_at")? {
+        "created_at"
+    } else {
+        "CURRENT_TIMESTAMP"
+    };
+
+    let sql = match table {
+        "entries" => {
+            let last_reviewed = if has_column(tx, &legacy, "last_reviewed")? {
+                "last_reviewed"
+            } else {
+                "NULL"
+            };
+            format!(
+                "INSERT INTO entries (id, content, date, created_at, last_reviewed)
+                 SELECT id, content, date, {created_at}, {last_reviewed} FROM {legacy}"
+            )
+        }
+        "people" | "projects" | "tags" => format!(
+            "INSERT INTO {table} (id, name, created_at) SELECT id, name, {created_at} FROM {legacy}"
+        ),
+        "entry_people" => link_copy_sql(&legacy, table, "person_id", "people"),
+        "entry_projects" => link_copy_sql(&legacy, table, "project_id", "projects"),
+        "entry_tags" => link_copy_sql(&legacy, table, "tag_id", "tags"),
+        _ => unreachable!("unknown legacy table {table}"),
+    };
+
+    tx.execute_batch(&sql)
+}
+
+fn link_copy_sql(legacy: &str, table: &str, column: &str, entity_table: &str) -> String {
+    format!(
+        "INSERT OR IGNORE INTO {table} (entry_id, {column})
+         SELECT entry_id, {column} FROM {legacy}
+         WHERE entry_id IN (SELECT id FROM entries)
+           AND {column} IN (SELECT id FROM {entity_table})"
+    )
+}
@@ -0,0 +1,79 @@
+use regex::{Captures, Regex};
+
+/// Total minutes of the time spent noted in `text`, or `None` if it notes
+/// none. Understands durations (`~1h30m`, `~45m`, `~2h`, `~1.5h`) and time
+/// ranges (`[09:00-10:30]`); a range ending before it starts runs past
+/// midnight. Several markers in one entry add up.
+pub fn minutes(text: &str) -> Option<i64> {
+    let mut total = None;
+    for caps in pattern().captures_iter(text) {
+        let minutes = if caps.get(3).is_some() {
+            let time = |hour: usize, minute: usize| -> Option<i64> {
+                let (hour, minute) = (
+                    caps[hour].parse::<i64>().ok()?,
+                    caps[minute].parse::<i64>().ok()?,
+                );
+                (hour < 24 && minute < 60).then_some(hour * 60 + minute)
+            };
+            match (time(3, 4), time(5, 6)) {
+                (Some(start), Some(end)) => (end - start).rem_euclid(24 * 60),
+                _ => continue,
+            }
+        } else {
+            let hours = caps.get(1).map(|h| h.as_str().parse::<f64>());
+            let minutes = caps.get(2).map(|m| m.as_str().parse::<i64>());
+            if hours.is_none() && minutes.is_none() {
+                continue;
+            }
+            // Durations too long to count in minutes are not durations.
+            let from_hours = match hours {
+                Some(Ok(hours)) if hours * 60.0 < i64::MAX as f64 => (hours * 60.0).round() as i64,
+                Some(_) => continue,
+                None => 0,
+            };
+            let minutes = minutes.unwrap_or(Ok(0)).ok();
+            match minutes.and_then(|minutes| from_hours.checked_add(minutes)) {
+                Some(minutes) => minutes,
+                None => continue,
+            }
+        };
+        if let Some(sum) = total.unwrap_or(0_i64).checked_add(minutes) {
+            total = Some(sum);
+        }
+    }
+    total
+}
+
+fn pattern() -> Regex {
+    Regex::new(
+        r"(?:^|[\s(,;])~(?:(\d+(?:\.\d+)?)h)?(?:(\d+)m)?\b|\[(\d{1,2}):(\d{2}) ?[-–] ?(\d{1,2}):(\d{2})\]",
+    )
+    .unwrap()
+}
+
+/// `text` without its duration markers.
+pub fn strip(text: &str) -> String {
+    let stripped = pattern().replace_all(text, |caps: &Captures| {
+        // Keep the separator the marker follows.
+        match caps[0].chars().next() {
+            Some(c) if c != '~' && c != '[' => c.to_string(),
+            _ => String::new(),
+        }
+    });
+    stripped.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Renders minutes as `1h 30m`, `2h` or `45m`.
+pub fn format_minutes(minutes: i64) -> String {
+    match (minutes / 60, minutes % 60) {
+        (0, minutes) => format!("{minutes}m"),
+        (hours, 0) => format!("{hours}h"),
+        (hours, minutes) => format!("{hours}h {minutes}m"),
+    }
+}
+
+/// Renders minutes as a duration marker that [`minutes`] reads back, such as
+/// `~1h30m`.
+pub fn marker(minutes: i64) -> String {
+    format!("~{}", format_minutes(minutes).replace(' ', ""))
+}
@@ -0,0 +1,35 @@
+use anyhow::{anyhow, Context};
+use std::env;
+use std::fs;
+use std::process::Command;
+
+/// Opens `initial` in the user's `$VISUAL` or `$EDITOR` (falling back to `vi`)
+/// and returns the saved text with trailing whitespace removed.
+pub fn edit_text(initial: &str) -> anyhow::Result<String> {
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    let mut parts = editor.split_whitespace();
+    let program = parts
+        .next()
+        .ok_or_else(|| anyhow!("Editor command is empty"))?;
+
+    let path = env::temp_dir().join(format!("dia-entry-{}.md", std::process::id()));
+    fs::write(&path, initial)?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(&path)
+        .status()
+        .with_context(|| format!("Failed to launch editor '{}'", editor));
+
+    let text = fs::read_to_string(&path);
+    let _ = fs::remove_file(&path);
+
+    if !status?.success() {
+        return Err(anyhow!("Editor '{}' exited with an error", editor));
+    }
+
+    Ok(text?.trim_end().to_string())
+}
@@ -1,37 +1,223 @@
 // src/entity.rs
-use serde::{Serialize, Deserialize};
+use crate::core::Entry;
+use crate::output::{join_list, CsvRecord};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Entry {
-    pub id: i64,
-    pub date: String,
-    pub content: String,
+/// The three kinds of entity an entry can mention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntityKind {
+    Person,
+    Project,
+    Tag,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Project {
-    pub id: i64,
-    pub name: String,
+impl EntityKind {
+    pub const ALL: [EntityKind; 3] = [EntityKind::Person, EntityKind::Project, EntityKind::Tag];
+
+    /// The character that introduces this kind of entity in entry text.
+    pub fn marker(self) -> char {
+        match self {
+            EntityKind::Person => '@',
+            EntityKind::Project => '%',
+            EntityKind::Tag => '#',
+        }
+    }
+
+    pub fn from_marker(marker: char) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.marker() == marker)
+    }
+
+    /// Splits a reference such as `@Alice` into its kind and name.
+    pub fn parse_reference(reference: &str) -> Option<(Self, &str)> {
+        let marker = reference.chars().next()?;
+        let kind = Self::from_marker(marker)?;
+        Some((kind, &reference[marker.len_utf8()..]))
+    }
+
+    /// Singular noun used in messages.
+    pub fn noun(self) -> &'static str {
+        match self {
+            EntityKind::Person => "person",
+            EntityKind::Project => "project",
+            EntityKind::Tag => "tag",
+        }
+    }
+
+    /// Whether names of this kind form `/`-separated hierarchies, such as
+    /// `%client/website`. People are never nested.
+    pub fn is_hierarchical(self) -> bool {
+        self != EntityKind::Person
+    }
+
+    /// Table holding the entities themselves.
+    pub fn table(self) -> &'static str {
+        match self {
+            EntityKind::Person => "people",
+            EntityKind::Project => "projects",
+            EntityKind::Tag => "tags",
+        }
+    }
+
+    /// Join table linking entries to entities of this kind.
+    pub fn link_table(self) -> &'static str {
+        match self {
+            EntityKind::Person => "entry_people",
+            EntityKind::Project => "entry_projects",
+            EntityKind::Tag => "entry_tags",
+        }
+    }
+
+    /// Column of [`EntityKind::link_table`] referencing the entity.
+    pub fn id_column(self) -> &'static str {
+        match self {
+            EntityKind::Person => "person_id",
+            EntityKind::Project => "project_id",
+            EntityKind::Tag => "tag_id",
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct Tag {
-    pub id: i64,
-    pub name: String,
+/// The name one level up a `/`-separated hierarchy: `client` for
+/// `client/website`.
+pub fn parent_name(name: &str) -> Option<&str> {
+    name.rsplit_once('/').map(|(parent, _)| parent)
+}
+
+/// The last segment of a `/`-separated name: `website` for `client/website`.
+pub fn leaf_name(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
 }
 
+/// A person, project or tag together with the number of entries mentioning
+/// it and the dates of the first and last of them.
 #[derive(Debug, Serialize, Deserialize)]
-pub struct Person {
+pub struct Entity {
     pub id: i64,
     pub name: String,
+    pub entries: i64,
+    #[serde(default)]
+    pub first_seen: Option<String>,
+    #[serde(default)]
+    pub last_used: Option<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct EntryWithRelations {
+    #[serde(default)]
     pub id: i64,
     pub date: String,
+    pub created_at: String,
     pub content: String,
+    #[serde(default)]
     pub projects: Vec<String>,
+    #[serde(default)]
     pub tags: Vec<String>,
+    #[serde(default)]
     pub people: Vec<String>,
+    /// Time spent, from duration markers in the content.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub minutes: Option<i64>,
+}
+
+impl EntryWithRelations {
+    /// Attaches the linked people, projects and tags to each entry.
+    pub fn from_entries(conn: &Connection, entries: Vec<Entry>) -> rusqlite::Result<Vec<Self>> {
+        let mut people = conn.prepare(
+            "SELECT x.name FROM entry_people l JOIN people x ON x.id = l.person_id
+             WHERE l.entry_id = ? ORDER BY x.name",
+        )?;
+        let mut projects = conn.prepare(
+            "SELECT x.name FROM entry_projects l JOIN projects x ON x.id = l.project_id
+             WHERE l.entry_id = ? ORDER BY x.name",
+        )?;
+        let mut tags = conn.prepare(
+            "SELECT x.name FROM entry_tags l JOIN tags x ON x.id = l.tag_id
+             WHERE l.entry_id = ? ORDER BY x.name",
+        )?;
+
+        let names = |stmt: &mut rusqlite::Statement, id: i64| {
+            stmt.query_map(params![id], |row| row.get(0))?
+                .collect::<rusqlite::Result<Vec<String>>>()
+        };
+
+        entries
+            .into_iter()
+            .map(|entry| {
+                Ok(Self {
+                    id: entry.id,
+                    date: entry.date.to_string(),
+                    created_at: entry.created_at.to_rfc3339(),
+                    projects: names(&mut projects, entry.id)?,
+                    tags: names(&mut tags, entry.id)?,
+                    people: names(&mut people, entry.id)?,
+                    minutes: entry.minutes,
+                    content: entry.content,
+                })
+            })
+            .collect()
+    }
+}
+
+impl CsvRecord for EntryWithRelations {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "date",
+        "created_at",
+        "content",
+        "projects",
+        "tags",
+        "people",
+        "minutes",
+    ];
+
+    fn csv_row(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            self.date.clone(),
+            self.created_at.clone(),
+            self.content.clone(),
+            join_list(&self.projects),
+            join_list(&self.tags),
+            join_list(&self.people),
+            self.minutes
+                .map(|minutes| minutes.to_string())
+                .unwrap_or_default(),
+        ]
+    }
+}
+
+impl CsvRecord for Entity {
+    const HEADERS: &'static [&'static str] = &[
+        "id",
+        "name",
+        "entries",
+        "first_seen",
+        "last_used",
+        "archived",
+        "metadata",
+    ];
+
+    fn csv_row(&self) -> Vec<String> {
+        let metadata = self
+            .metadata
+            .iter()
+            .map(|(key, value)| format!("{key}={value}"))
+            .collect::<Vec<_>>();
+        vec![
+            self.id.to_string(),
+            self.name.clone(),
+            self.entries.to_string(),
+            self.first_seen.clone().unwrap_or_default(),
+            self.last_used.clone().unwrap_or_default(),
+            self.archived.to_string(),
+            join_list(&metadata),
+        ]
+    }
 }
@@ -0,0 +1,506 @@
+use crate::archive::Archive;
+use crate::core::Core;
+use crate::dates;
+use crate::entity::{EntityKind, EntryWithRelations};
+use crate::filter::EntryFilter;
+use crate::markers::NormalizationPolicy;
+use crate::metadata::{self, Metadata};
+use anyhow::anyhow;
+use chrono::{Local, NaiveDate};
+use clap::ValueEnum;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    /// Markdown document
+    #[default]
+    Markdown,
+    /// Standalone HTML page
+    Html,
+    /// Lossless JSON archive, readable by `dia import`
+    Json,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum GroupBy {
+    /// One section per day
+    #[default]
+    Day,
+    /// One section per project
+    Project,
+}
+
+/// The three entity kinds, in index order: (marker, title, anchor prefix).
+const KINDS: [(char, &str, &str); 3] = [
+    ('@', "People", "person"),
+    ('%', "Projects", "project"),
+    ('#', "Tags", "tag"),
+];
+
+const NO_PROJECT: &str = "No project";
+
+pub fn handle_export_command(
+    core: &Core,
+    format: ExportFormat,
+    from: Option<String>,
+    to: Option<String>,
+    group_by: GroupBy,
+    output: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    let from = from.map(|d| dates::parse_span(&d, today)).transpose()?;
+    let to = to.map(|d| dates::parse_span(&d, today)).transpose()?;
+    if let (Some((start, _)), Some((_, end))) = (from, to) {
+        if start > end {
+            return Err(anyhow!("Invalid date range: {} is after {}", start, end));
+        }
+    }
+
+    let filter = EntryFilter {
+        dates: (from.is_some() || to.is_some()).then(|| {
+            (
+                from.map_or(NaiveDate::from_ymd_opt(1, 1, 1).unwrap(), |(start, _)| {
+                    start
+                }),
+                to.map_or(
+                    NaiveDate::from_ymd_opt(9999, 12, 31).unwrap(),
+                    |(_, end)| end,
+                ),
+            )
+        }),
+        ..Default::default()
+    };
+
+    let (rendered, count) = match format {
+        ExportFormat::Json => {
+            let archive = Archive::load(core, &filter)?;
+            let count = archive.entries.len();
+            (serde_json::to_string_pretty(&archive)? + "\n", count)
+        }
+        ExportFormat::Markdown | ExportFormat::Html => {
+            let entries =
+                EntryWithRelations::from_entries(&core.conn, filter.entries(&core.conn)?)?;
+            let title = match (from, to) {
+                (Some((start, _)), Some((_, end))) => format!("Diary {} to {}", start, end),
+                (Some((start, _)), None) => format!("Diary since {}", start),
+                (None, Some((_, end))) => format!("Diary until {}", end),
+                (None, None) => "Diary".to_string(),
+            };
+
+            let metadata = [
+                metadata::load(&core.conn, EntityKind::Person)?,
+                metadata::load(&core.conn, EntityKind::Project)?,
+                metadata::load(&core.conn, EntityKind::Tag)?,
+            ];
+            let document = Document::new(title, &entries, group_by, metadata, &core.policy);
+            let rendered = if format == ExportFormat::Html {
+                document.to_html()
+            } else {
+                document.to_markdown()
+            };
+            (rendered, entries.len())
+        }
+    };
+
+    match output {
+        Some(path) => {
+            fs::write(&path, rendered)?;
+            println!("Exported {} entries to {}", count, path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+/// Entries arranged into titled sections, plus an index of every entity
+/// mentioned and the sections it appears in.
+struct Document<'a> {
+    title: String,
+    group_by: GroupBy,
+    sections: Vec<(String, Vec<&'a EntryWithRelations>)>,
+    index: [BTreeMap<String, Vec<String>>; 3],
+    /// Metadata of the indexed entities, in the same order as `index`.
+    metadata: [BTreeMap<String, Metadata>; 3],
+    /// Anchor of every section and indexed entity, by prefix (`section` or
+    /// the entity noun) and name. Each is unique within the document.
+    anchors: HashMap<(&'static str, String), String>,
+    policy: &'a NormalizationPolicy,
+}
+
+impl<'a> Document<'a> {
+    fn new(
+        title: String,
+        entries: &'a [EntryWithRelations],
+        group_by: GroupBy,
+        metadata: [BTreeMap<String, Metadata>; 3],
+        policy: &'a NormalizationPolicy,
+    ) -> Self {
+        let mut sections: BTreeMap<String, Vec<&EntryWithRelations>> = BTreeMap::new();
+        for entry in entries {
+            match group_by {
+                GroupBy::Day => sections.entry(entry.date.clone()).or_default().push(entry),
+                GroupBy::Project if entry.projects.is_empty() => sections
+                    .entry(NO_PROJECT.to_string())
+                    .or_default()
+                    .push(entry),
+                GroupBy::Project => {
+                    for project in &entry.projects {
+                        sections.entry(project.clone()).or_default().push(entry);
+                    }
+                }
+            }
+        }
+
+        // Keep "No project" last rather than sorted among the names.
+        let mut sections = sections.into_iter().collect::<Vec<_>>();
+        if let Some(index) = sections.iter().position(|(name, _)| name == NO_PROJECT) {
+            let unassigned = sections.remove(index);
+            sections.push(unassigned);
+        }
+
+        let mut index: [BTreeMap<String, Vec<String>>; 3] = Default::default();
+        for (section, section_entries) in &sections {
+            for entry in section_entries {
+                for (names, kind_index) in
+                    [(&entry.people, 0), (&entry.projects, 1), (&entry.tags, 2)]
+                {
+                    for name in names {
+                        let sections = index[kind_index].entry(name.clone()).or_default();
+                        if sections.last() != Some(section) {
+                            sections.push(section.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        let mut anchors = HashMap::new();
+        let mut taken = HashSet::new();
+        for (section, _) in &sections {
+            let anchor = unique_anchor(&mut taken, "section", section);
+            anchors.insert(("section", section.clone()), anchor);
+        }
+        for ((_, _, kind), names) in KINDS.iter().zip(&index) {
+            for name in names.keys() {
+                let anchor = unique_anchor(&mut taken, kind, name);
+                anchors.insert((*kind, name.clone()), anchor);
+            }
+        }
+
+        Self {
+            title,
+            group_by,
+            sections,
+            index,
+            metadata,
+            anchors,
+            policy,
+        }
+    }
+
+    fn section_heading(&self, section: &str) -> String {
+        match self.group_by {
+            GroupBy::Day => NaiveDate::parse_from_str(section, "%Y-%m-%d")
+                .map(|date| date.format("%Y-%m-%d (%A)").to_string())
+                .unwrap_or_else(|_| section.to_string()),
+            GroupBy::Project if section == NO_PROJECT => section.to_string(),
+            GroupBy::Project => format!("%{}", section),
+        }
+    }
+
+    /// Short form of the heading, used for links from the index.
+    fn section_label(&self, section: &str) -> String {
+        match self.group_by {
+            GroupBy::Day => section.to_string(),
+            GroupBy::Project => self.section_heading(section),
+        }
+    }
+
+    fn section_anchor(&self, section: &str) -> &str {
+        self.anchor("section", section)
+    }
+
+    fn anchor(&self, prefix: &'static str, name: &str) -> &str {
+        self.anchors
+            .get(&(prefix, name.to_string()))
+            .map_or(prefix, String::as_str)
+    }
+
+    /// Rewrites the entity markers of an entry that are actually linked to it,
+    /// leaving any other `@`, `%` or `#` text alone.
+    fn render_content(
+        &self,
+        entry: &EntryWithRelations,
+        render: impl Fn(char, &str, &'static str) -> String,
+        escape: impl Fn(&str) -> String,
+    ) -> String {
+        let mut rendered = String::new();
+        let mut last = 0;
+        for marker in self.policy.extract(&entry.content) {
+            let names = match marker.kind {
+                EntityKind::Person => &entry.people,
+                EntityKind::Project => &entry.projects,
+                EntityKind::Tag => &entry.tags,
+            };
+            let key = self.policy.key(&marker.name);
+            let Some(name) = names.iter().find(|n| self.policy.key(n) == key) else {
+                continue;
+            };
+
+            rendered.push_str(&escape(&entry.content[last..marker.start]));
+            rendered.push_str(&render(marker.kind.marker(), name, marker.kind.noun()));
+            last = marker.end;
+        }
+        rendered.push_str(&escape(&entry.content[last..]));
+        rendered
+    }
+
+    fn to_markdown(&self) -> String {
+        let mut out = format!("# {}\n", self.title);
+
+        if self.sections.is_empty() {
+            out.push_str("\nNo entries.\n");
+            return out;
+        }
+
+        for (section, entries) in &self.sections {
+            out.push_str(&format!(
+                "\n## <a id=\"{}\"></a>{}\n\n",
+                self.section_anchor(section),
+                escape_markdown(&self.section_heading(section))
+            ));
+            for entry in entries {
+                let content = self.render_content(
+                    entry,
+                    |marker, name, kind| {
+                        format!(
+                            "[{}{}](#{})",
+                            marker,
+                            escape_markdown(name),
+                            self.anchor(kind, name)
+                        )
+                    },
+                    escape_markdown,
+                );
+                let content = content.replace('\n', "\n  ");
+                match self.group_by {
+                    GroupBy::Day => out.push_str(&format!("- {}\n", content)),
+                    GroupBy::Project => {
+                        out.push_str(&format!("- **{}**: {}\n", entry.date, content))
+                    }
+                }
+            }
+        }
+
+        out.push_str("\n---\n\n## Index\n");
+        for (((marker, title, kind), names), metadata) in
+            KINDS.iter().zip(&self.index).zip(&self.metadata)
+        {
+            if names.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n### {}\n\n", title));
+            for (name, sections) in names {
+                let links = sections
+                    .iter()
+                    .map(|s| {
+                        format!(
+                            "[{}](#{})",
+                            escape_markdown(&self.section_label(s)),
+                            self.section_anchor(s)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                let details = metadata
+                    .get(name)
+                    .map(|metadata| format!(" ({})", escape_markdown(&metadata.summary())))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "- <a id=\"{}\"></a>**{}{}**{}: {}\n",
+                    self.anchor(kind, name),
+                    marker,
+                    escape_markdown(name),
+                    details,
+                    links
+                ));
+            }
+        }
+
+        out
+    }
+
+    fn to_html(&self) -> String {
+        let mut out = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{title}</title>\n<style>\n{style}</style>\n</head>\n<body>\n<h1>{title}</h1>\n",
+            title = escape_html(&self.title),
+            style = HTML_STYLE
+        );
+
+        if self.sections.is_empty() {
+            out.push_str("<p>No entries.</p>\n");
+        }
+
+        for (section, entries) in &self.sections {
+            out.push_str(&format!(
+                "<section id=\"{}\">\n<h2>{}</h2>\n<ul>\n",
+                self.section_anchor(section),
+                escape_html(&self.section_heading(section))
+            ));
+            for entry in entries {
+                let content = self.render_content(
+                    entry,
+                    |marker, name, kind| {
+                        format!(
+                            "<a class=\"badge {kind}\" href=\"#{}\">{}{}</a>",
+                            self.anchor(kind, name),
+                            marker,
+                            escape_html(name)
+                        )
+                    },
+                    escape_html,
+                );
+                let content = content.replace('\n', "<br>\n");
+                match self.group_by {
+                    GroupBy::Day => out.push_str(&format!("<li>{}</li>\n", content)),
+                    GroupBy::Project => out.push_str(&format!(
+                        "<li><time>{}</time> {}</li>\n",
+                        entry.date, content
+                    )),
+                }
+            }
+            out.push_str("</ul>\n</section>\n");
+        }
+
+        if self.index.iter().any(|names| !names.is_empty()) {
+            out.push_str("<hr>\n<section id=\"index\">\n<h2>Index</h2>\n");
+            for (((marker, title, kind), names), metadata) in
+                KINDS.iter().zip(&self.index).zip(&self.metadata)
+            {
+                if names.is_empty() {
+                    continue;
+                }
+                out.push_str(&format!("<h3>{}</h3>\n<ul>\n", title));
+                for (name, sections) in names {
+                    let links = sections
+                        .iter()
+                        .map(|s| {
+                            format!(
+                                "<a href=\"#{}\">{}</a>",
+                                self.section_anchor(s),
+                                escape_html(&self.section_label(s))
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    let details = metadata
+                        .get(name)
+                        .map(|metadata| {
+                            format!(
+                                " <span class=\"details\">{}</span>",
+                                escape_html(&metadata.summary())
+                            )
+                        })
+                        .unwrap_or_default();
+                    out.push_str(&format!(
+                        "<li id=\"{}\"><span class=\"badge {kind}\">{}{}</span>{} {}</li>\n",
+                        self.anchor(kind, name),
+                        marker,
+                        escape_html(name),
+                        details,
+                        links
+                    ));
+                }
+                out.push_str("</ul>\n");
+            }
+            out.push_str("</section>\n");
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+}
+
+const HTML_STYLE: &str =
+    "body { font-family: sans-serif; max-width: 50em; margin: 2em auto; line-height: 1.5; }
+.badge { border-radius: 0.3em; padding: 0 0.3em; text-decoration: none; color: inherit; }
+.person { background: #d9f2f7; }
+.project { background: #fcefc7; }
+.tag { background: #dcf5dc; }
+time { color: #666; margin-right: 0.5em; }
+.details { color: #666; }
+";
+
+/// `prefix-slug` for `name`, with a `-2`, `-3`, ... suffix if another
+/// section or entity already has that anchor.
+fn unique_anchor(taken: &mut HashSet<String>, prefix: &str, name: &str) -> String {
+    let slug = slug(name);
+    let base = if slug.is_empty() {
+        prefix.to_string()
+    } else {
+        format!("{prefix}-{slug}")
+    };
+    let mut anchor = base.clone();
+    let mut suffix = 2;
+    while !taken.insert(anchor.clone()) {
+        anchor = format!("{base}-{suffix}");
+        suffix += 1;
+    }
+    anchor
+}
+
+/// Lower-case, dash-separated identifier for anchors.
+fn slug(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.chars().flat_map(char::to_lowercase) {
+        if c.is_alphanumeric() {
+            slug.push(c);
+        } else if !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_matches('-').to_string()
+}
+
+/// Backslash-escapes the characters Markdown would read as formatting, plus a
+/// `-` or `+` that would start a list.
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut line_start = true;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let starts_list = line_start
+            && matches!(c, '-' | '+')
+            && chars.peek().is_none_or(|next| next.is_whitespace());
+        if starts_list
+            || matches!(
+                c,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|'
+            )
+        {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        line_start = c == '\n';
+    }
+    escaped
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,127 @@
+use crate::alias;
+use crate::core::Entry;
+use crate::entity::EntityKind;
+use chrono::NaiveDate;
+use rusqlite::{params_from_iter, Connection};
+
+/// Entry filters shared by `show`, `review` and friends. Every field is
+/// optional and all set fields must match.
+#[derive(Default)]
+pub struct EntryFilter {
+    /// Inclusive date range
+    pub dates: Option<(NaiveDate, NaiveDate)>,
+    /// Words that must all appear, matched through `entries_fts`
+    pub search: Option<String>,
+    pub person: Option<String>,
+    pub project: Option<String>,
+    pub tag: Option<String>,
+    /// Entries with at least one of these tags
+    pub any_tag: Vec<String>,
+}
+
+impl EntryFilter {
+    /// Returns SQL conditions over `entries e` together with their bound
+    /// parameters, ready to be joined with `AND`. Entity names given as
+    /// aliases are resolved to their canonical names.
+    pub fn conditions(&self, conn: &Connection) -> rusqlite::Result<(Vec<String>, Vec<String>)> {
+        let resolve = |kind: EntityKind, name: &str| {
+            alias::resolve(conn, kind, &strip_marker(name, kind.marker()))
+        };
+        let mut conditions = Vec::new();
+        let mut params = Vec::new();
+
+        if let Some((from, to)) = self.dates {
+            conditions.push("e.date BETWEEN ? AND ?".to_string());
+            params.push(from.to_string());
+            params.push(to.to_string());
+        }
+
+        // Blank search text matches everything.
+        if let Some(search) = self.search.as_ref().filter(|s| !s.trim().is_empty()) {
+            conditions.push(
+                "e.id IN (SELECT rowid FROM entries_fts WHERE entries_fts MATCH ?)".to_string(),
+            );
+            params.push(fts_query(search));
+        }
+
+        if let Some(person) = &self.person {
+            conditions.push(
+                "EXISTS (SELECT 1 FROM entry_people ep JOIN people p ON p.id = ep.person_id \
+                 WHERE ep.entry_id = e.id AND entity_key(p.name) = entity_key(?))"
+                    .to_string(),
+            );
+            params.push(resolve(EntityKind::Person, person)?);
+        }
+
+        if let Some(project) = &self.project {
+            conditions.push(subtree_condition(EntityKind::Project));
+            params.push(resolve(EntityKind::Project, project)?);
+        }
+
+        if let Some(tag) = &self.tag {
+            conditions.push(subtree_condition(EntityKind::Tag));
+            params.push(resolve(EntityKind::Tag, tag)?);
+        }
+
+        if !self.any_tag.is_empty() {
+            let alternatives = vec![subtree_condition(EntityKind::Tag); self.any_tag.len()];
+            conditions.push(format!("({})", alternatives.join(" OR ")));
+            for tag in &self.any_tag {
+                params.push(resolve(EntityKind::Tag, tag)?);
+            }
+        }
+
+        Ok((conditions, params))
+    }
+
+    /// Fetches all matching entries in chronological order.
+    pub fn entries(&self, conn: &Connection) -> rusqlite::Result<Vec<Entry>> {
+        let (conditions, params) = self.conditions(conn)?;
+
+        let mut query = format!("SELECT {} FROM entries e", Entry::COLUMNS);
+        if !conditions.is_empty() {
+            query.push_str(" WHERE ");
+            query.push_str(&conditions.join(" AND "));
+        }
+        query.push_str(" ORDER BY e.date, e.id");
+
+        let mut stmt = conn.prepare(&query)?;
+        let entries = stmt.query_map(params_from_iter(params.iter()), Entry::from_row)?;
+        entries.collect()
+    }
+}
+
+/// Matches entries linked to the named project or tag, or to anything nested
+/// below it, so `client` also matches `client/website`.
+fn subtree_condition(kind: EntityKind) -> String {
+    let (table, link_table, id_column) = (kind.table(), kind.link_table(), kind.id_column());
+    format!(
+        "EXISTS (SELECT 1 FROM {link_table} l WHERE l.entry_id = e.id AND l.{id_column} IN (
+             WITH RECURSIVE tree(id) AS (
+                 SELECT id FROM {table} WHERE entity_key(name) = entity_key(?)
+                 UNION SELECT x.id FROM {table} x JOIN tree ON x.parent_id = tree.id
+             )
+             SELECT id FROM tree))"
+    )
+}
+
+/// Turns search text into an FTS5 query matching entries that contain every
+/// word. Each word is quoted, so `follow-up` or `don't` are searched for
+/// rather than read as query syntax.
+fn fts_query(search: &str) -> String {
+    search
+        .split_whitespace()
+        .map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Accepts entity filters with or without their marker (`@John` or `John`),
+/// and with or without quotes (`@"Anna Maria"`).
+fn strip_marker(name: &str, marker: char) -> String {
+    let name = name.strip_prefix(marker).unwrap_or(name);
+    name.strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(name)
+        .to_string()
+}
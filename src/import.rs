@@ -0,0 +1,348 @@
+use crate::archive::{self, Archive, ImportSummary};
+use crate::core::Core;
+use crate::entity::EntityKind;
+use crate::markers::NormalizationPolicy;
+use anyhow::{anyhow, Context};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use clap::ValueEnum;
+use comfy_table::Table;
+use regex::Regex;
+use rusqlite::Transaction;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ImportSource {
+    /// Archive written by `dia export --format json`
+    #[default]
+    Json,
+    /// Plain-text jrnl journal (`jrnl --export txt` or the journal file)
+    Jrnl,
+    /// Day One JSON export
+    Dayone,
+    /// Directory of `YYYY-MM-DD.md` daily notes
+    DailyNotes,
+}
+
+/// An entry parsed from another tool's format, before it is logged.
+#[derive(Debug, Clone, PartialEq)]
+struct ImportedEntry {
+    date: NaiveDate,
+    /// UTC creation time, when the source records one.
+    created_at: Option<NaiveDateTime>,
+    content: String,
+}
+
+pub fn handle_import_command(
+    core: &mut Core,
+    path: &Path,
+    source: ImportSource,
+    links_as_projects: bool,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    let mut entries = match source {
+        ImportSource::Json => Vec::new(),
+        ImportSource::Jrnl => parse_jrnl(&read(path)?)?,
+        ImportSource::Dayone => parse_dayone(&read(path)?, &core.policy)?,
+        ImportSource::DailyNotes => read_daily_notes(path)?,
+    };
+    if links_as_projects {
+        let links = Regex::new(r"\[\[([^\]|#]+)(?:#[^\]|]*)?(?:\|[^\]]*)?\]\]").unwrap();
+        for entry in &mut entries {
+            entry.content = links
+                .replace_all(&entry.content, |caps: &regex::Captures| {
+                    format!("%{}", link_to_name(&caps[1]))
+                })
+                .into_owned();
+        }
+    }
+
+    // Everything happens in one transaction, which a dry run rolls back.
+    let tx = core.conn.transaction()?;
+    let summary = match source {
+        ImportSource::Json => {
+            let archive: Archive = serde_json::from_str(&read(path)?)
+                .with_context(|| format!("{} is not a dia JSON archive", path.display()))?;
+            archive.restore(&tx)?
+        }
+        _ => insert_entries(&tx, &core.policy, entries)?,
+    };
+
+    if dry_run {
+        if !summary.imported.is_empty() {
+            let mut table = Table::new();
+            table.set_header(vec!["Date", "Entry"]);
+            for (date, content) in &summary.imported {
+                table.add_row(vec![date.to_string(), content.clone()]);
+            }
+            println!("{table}");
+        }
+        println!(
+            "Dry run: would import {} entries ({} duplicates skipped).",
+            summary.imported.len(),
+            summary.duplicates
+        );
+        return Ok(());
+    }
+
+    tx.commit()?;
+    println!(
+        "Imported {} entries ({} duplicates skipped).",
+        summary.imported.len(),
+        summary.duplicates
+    );
+    Ok(())
+}
+
+fn read(path: &Path) -> anyhow::Result<String> {
+    fs::read_to_string(path).with_context(|| format!("Cannot read {}", path.display()))
+}
+
+/// Logs parsed entries the way `dia log` would, skipping any whose date and
+/// content are already in the diary.
+fn insert_entries(
+    tx: &Transaction,
+    policy: &NormalizationPolicy,
+    entries: Vec<ImportedEntry>,
+) -> anyhow::Result<ImportSummary> {
+    let mut seen = archive::existing_fingerprints(tx)?;
+    let mut summary = ImportSummary::default();
+
+    for entry in entries {
+        if !seen.insert(archive::fingerprint(
+            &entry.date.to_string(),
+            &entry.content,
+        )) {
+            summary.duplicates += 1;
+            continue;
+        }
+        Core::insert_entry(tx, policy, &entry.content, entry.date, entry.created_at)?;
+        summary.imported.push((entry.date, entry.content));
+    }
+
+    Ok(summary)
+}
+
+/// Turns the target of a `[[link]]` into a project name: folders and
+/// punctuation become dashes, so `[[Clients/Big Co]]` maps to `%Clients-Big-Co`.
+fn link_to_name(target: &str) -> String {
+    let mut name = String::new();
+    for c in target.trim().chars() {
+        if c.is_alphanumeric() || c == '_' {
+            name.push(c);
+        } else if !name.ends_with('-') {
+            name.push('-');
+        }
+    }
+    name.trim_matches('-').to_string()
+}
+
+fn local_to_utc(local: NaiveDateTime) -> NaiveDateTime {
+    Local
+        .from_local_datetime(&local)
+        .earliest()
+        .map(|time| time.naive_utc())
+        .unwrap_or(local)
+}
+
+/// Parses jrnl's plain-text format: each entry starts with a
+/// `[YYYY-MM-DD HH:MM] Title` line (brackets optional, 12- or 24-hour time)
+/// and runs until the next one.
+fn parse_jrnl(text: &str) -> anyhow::Result<Vec<ImportedEntry>> {
+    let header = Regex::new(
+        r"^\[?(\d{4}-\d{2}-\d{2})[ T](\d{1,2}:\d{2}(?::\d{2})?(?: ?[AaPp][Mm])?)\]?(?: (.*))?$",
+    )
+    .unwrap();
+
+    let mut entries = Vec::new();
+    let mut current: Option<(NaiveDateTime, Vec<&str>)> = None;
+
+    for (number, line) in text.lines().enumerate() {
+        if let Some(caps) = header.captures(line) {
+            let date = NaiveDate::parse_from_str(&caps[1], "%Y-%m-%d")
+                .with_context(|| format!("Line {}: invalid date", number + 1))?;
+            let time = parse_time(&caps[2])
+                .ok_or_else(|| anyhow!("Line {}: invalid time '{}'", number + 1, &caps[2]))?;
+
+            entries.extend(current.take().and_then(finish_jrnl_entry));
+            let title = caps.get(3).map_or("", |title| title.as_str());
+            current = Some((date.and_time(time), vec![title]));
+        } else if let Some((_, lines)) = &mut current {
+            lines.push(line);
+        } else if !line.trim().is_empty() {
+            return Err(anyhow!(
+                "Line {}: expected a '[YYYY-MM-DD HH:MM] title' line",
+                number + 1
+            ));
+        }
+    }
+    entries.extend(current.and_then(finish_jrnl_entry));
+
+    Ok(entries)
+}
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    let time = time.to_uppercase();
+    [
+        "%H:%M:%S",
+        "%H:%M",
+        "%I:%M:%S %p",
+        "%I:%M %p",
+        "%I:%M:%S%p",
+        "%I:%M%p",
+    ]
+    .iter()
+    .find_map(|format| NaiveTime::parse_from_str(&time, format).ok())
+}
+
+fn finish_jrnl_entry((timestamp, lines): (NaiveDateTime, Vec<&str>)) -> Option<ImportedEntry> {
+    let content = lines.join("\n").trim().to_string();
+    (!content.is_empty()).then(|| ImportedEntry {
+        date: timestamp.date(),
+        created_at: Some(local_to_utc(timestamp)),
+        content,
+    })
+}
+
+#[derive(Deserialize)]
+struct DayOneExport {
+    entries: Vec<DayOneEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DayOneEntry {
+    creation_date: DateTime<Utc>,
+    #[serde(default)]
+    text: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// Parses a Day One JSON export. Day One keeps tags outside the text, so they
+/// are appended as `#tag` markers unless the text already contains them.
+fn parse_dayone(text: &str, policy: &NormalizationPolicy) -> anyhow::Result<Vec<ImportedEntry>> {
+    let export: DayOneExport = serde_json::from_str(text).context("Not a Day One JSON export")?;
+    // Day One escapes Markdown punctuation in its text.
+    let escaped = Regex::new(r"\\([\\`*_{}\[\]()+\-.!])").unwrap();
+
+    Ok(export
+        .entries
+        .into_iter()
+        .filter_map(|entry| {
+            let mut content = escaped.replace_all(entry.text.trim(), "$1").into_owned();
+            let mut tagged = policy
+                .extract(&content)
+                .into_iter()
+                .filter(|marker| marker.kind == EntityKind::Tag)
+                .map(|marker| policy.key(&marker.name))
+                .collect::<HashSet<_>>();
+            for tag in &entry.tags {
+                let name = link_to_name(tag);
+                if !name.is_empty() && tagged.insert(policy.key(&name)) {
+                    if !content.is_empty() {
+                        content.push(' ');
+                    }
+                    content.push('#');
+                    content.push_str(&name);
+                }
+            }
+
+            (!content.is_empty()).then(|| ImportedEntry {
+                date: entry.creation_date.with_timezone(&Local).date_naive(),
+                created_at: Some(entry.creation_date.naive_utc()),
+                content,
+            })
+        })
+        .collect())
+}
+
+/// Reads every `YYYY-MM-DD.md` file under `path` (or `path` itself), oldest
+/// first, splitting each into entries with [`split_daily_note`].
+fn read_daily_notes(path: &Path) -> anyhow::Result<Vec<ImportedEntry>> {
+    let mut files = Vec::new();
+    collect_daily_notes(path, &mut files)?;
+    if files.is_empty() {
+        return Err(anyhow!(
+            "No YYYY-MM-DD.md notes found in {}",
+            path.display()
+        ));
+    }
+    files.sort();
+
+    let mut entries = Vec::new();
+    for (date, file) in files {
+        entries.extend(
+            split_daily_note(&read(&file)?)
+                .into_iter()
+                .map(|content| ImportedEntry {
+                    date,
+                    created_at: None,
+                    content,
+                }),
+        );
+    }
+    Ok(entries)
+}
+
+fn collect_daily_notes(path: &Path, files: &mut Vec<(NaiveDate, PathBuf)>) -> anyhow::Result<()> {
+    if path.is_dir() {
+        for child in
+            fs::read_dir(path).with_context(|| format!("Cannot read {}", path.display()))?
+        {
+            collect_daily_notes(&child?.path(), files)?;
+        }
+    } else if let Some(date) = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(".md"))
+        .and_then(|stem| NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok())
+    {
+        files.push((date, path.to_path_buf()));
+    }
+    Ok(())
+}
+
+/// Splits a daily note into entries: each top-level list item or paragraph
+/// becomes one entry, with indented lines kept as its continuation. Headings
+/// and YAML front matter are dropped.
+fn split_daily_note(text: &str) -> Vec<String> {
+    let heading = Regex::new(r"^#{1,6}\s").unwrap();
+    let item = Regex::new(r"^(?:[-*+]|\d+[.)])\s+(?:\[[ xX]\]\s+)?(.*)$").unwrap();
+
+    let mut lines = text.lines().peekable();
+    if lines.peek().map(|line| line.trim_end()) == Some("---") {
+        lines.next();
+        for line in lines.by_ref() {
+            if line.trim_end() == "---" {
+                break;
+            }
+        }
+    }
+
+    let mut entries = Vec::new();
+    let mut current: Vec<&str> = Vec::new();
+    let mut flush = |current: &mut Vec<&str>| {
+        let content = current.join("\n").trim().to_string();
+        if !content.is_empty() {
+            entries.push(content);
+        }
+        current.clear();
+    };
+
+    for line in lines {
+        if line.trim().is_empty() || heading.is_match(line) {
+            flush(&mut current);
+        } else if let Some(caps) = item.captures(line) {
+            flush(&mut current);
+            current.push(caps.get(1).map_or("", |text| text.as_str()));
+        } else {
+            current.push(line.trim());
+        }
+    }
+    flush(&mut current);
+
+    entries
+}
@@ -1,12 +1,32 @@
 use crate::core::Core;
+use crate::entity::EntityKind;
+use crate::output::OutputFormat;
 use clap::{Parser, Subcommand};
+use std::path::PathBuf;
 
-mod autocomplete;
+mod alias;
+mod archive;
+mod completion;
 mod config;
 mod core;
+mod dashboard;
+mod dates;
+mod db;
+mod duration;
+mod editor;
+mod entity;
+mod export;
+mod filter;
+mod import;
+mod markers;
+mod metadata;
+mod output;
+mod report;
 mod review;
 mod show;
 mod stats;
+mod template;
+mod timer;
 
 #[derive(Parser)]
 #[command(name = "dia")]
@@ -21,16 +41,73 @@ enum Commands {
     /// Open the database file
     Db,
 
-    /// Log a new diary entry
+    /// Log a new diary entry (interactively when no text is given)
     Log {
         /// The entry text with semantic tags
-        entry: String,
+        entry: Option<String>,
 
         #[arg(short, long)]
-        /// Specific date (YYYY-MM-DD)
+        /// Specific date (YYYY-MM-DD, "yesterday", "last friday", ...)
         date: Option<String>,
     },
 
+    /// Edit an existing entry, re-linking its entities
+    Edit {
+        /// ID of the entry (see `dia show entries`)
+        id: i64,
+
+        /// Replacement text; opens $EDITOR when omitted
+        entry: Option<String>,
+
+        #[arg(short, long)]
+        /// Move the entry to another date (YYYY-MM-DD, "yesterday", ...)
+        date: Option<String>,
+    },
+
+    /// Delete an entry
+    Delete {
+        /// ID of the entry (see `dia show entries`)
+        id: i64,
+
+        #[arg(short, long)]
+        /// Skip the confirmation prompt
+        yes: bool,
+    },
+
+    /// Start a timer; `dia stop` logs it as an entry with the time worked
+    Start {
+        /// What you are working on, with semantic tags
+        description: String,
+    },
+
+    /// Stop the timer and log an entry with its duration
+    Stop {
+        #[arg(long)]
+        /// Drop the timer without logging an entry
+        discard: bool,
+    },
+
+    /// Pause the running timer
+    Pause,
+
+    /// Resume the paused timer
+    Resume,
+
+    /// Show the running timer
+    Status,
+
+    /// Rename or merge people, projects and tags
+    Entity {
+        #[command(subcommand)]
+        action: EntityAction,
+    },
+
+    /// Manage alternative names for people, projects and tags
+    Alias {
+        #[command(subcommand)]
+        action: AliasAction,
+    },
+
     /// Show diary entries and entities
     Show {
         #[command(subcommand)]
@@ -42,10 +119,160 @@ enum Commands {
         #[arg(short, long)]
         /// Time period to analyze (e.g. "last week", "this month")
         period: Option<String>,
+
+        #[arg(short, long)]
+        /// Add bar charts and sparklines to the tables
+        chart: bool,
+
+        #[arg(long, default_value_t = 10)]
+        /// Number of people, projects and tags to list
+        top: usize,
+
+        #[arg(long)]
+        /// Count entries of nested projects and tags towards their parents
+        rollup: bool,
+    },
+
+    /// Print a report: time spent, a standup, a weekly summary, a brag
+    /// document or your own template
+    Report {
+        /// Report to print: time, standup, weekly, brag or the name of a
+        /// template in ~/.config/dia/templates
+        name: String,
+
+        #[arg(short, long)]
+        /// Period to report on (default: this week, or this year for brag;
+        /// the standup covers yesterday and today)
+        period: Option<String>,
+
+        #[arg(long)]
+        /// Count nested projects and tags towards their parents
+        rollup: bool,
+
+        #[arg(short, long, value_enum)]
+        /// Template to use: NAME.md or NAME.txt (default: whichever exists,
+        /// Markdown first)
+        format: Option<report::ReportFormat>,
     },
 
     /// Review entries in spaced repetition style
-    Review,
+    Review {
+        #[arg(short = 'P', long)]
+        /// Only review entries for this project (%name)
+        project: Option<String>,
+
+        #[arg(short, long)]
+        /// Only review entries with this tag (#name)
+        tag: Option<String>,
+
+        #[arg(short, long, default_value_t = 20)]
+        /// Maximum number of entries per session
+        limit: usize,
+    },
+
+    /// Export the diary as a Markdown or HTML document, or a JSON archive
+    Export {
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Document format
+        format: export::ExportFormat,
+
+        #[arg(long)]
+        /// First day to include (YYYY-MM-DD, "last month", ...)
+        from: Option<String>,
+
+        #[arg(long)]
+        /// Last day to include (YYYY-MM-DD, "yesterday", ...)
+        to: Option<String>,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Section the document by day or by project (Markdown and HTML)
+        group_by: export::GroupBy,
+
+        #[arg(short, long)]
+        /// Write to this file instead of stdout
+        output: Option<PathBuf>,
+    },
+
+    /// Import entries from a dia archive, jrnl, Day One or daily notes
+    Import {
+        /// File (or directory of daily notes) to read
+        path: PathBuf,
+
+        #[arg(long, value_enum, default_value_t)]
+        /// Format of the input
+        from: import::ImportSource,
+
+        #[arg(long)]
+        /// Turn [[wiki links]] into %project markers (jrnl, Day One, daily notes)
+        links_as_projects: bool,
+
+        #[arg(short = 'n', long)]
+        /// Show what would be imported without changing the diary
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum EntityAction {
+    /// Rename a person, project or tag
+    Rename {
+        /// Current name with its marker (@person, %project or #tag)
+        name: String,
+
+        /// New name (the marker is optional)
+        new_name: String,
+
+        #[arg(short, long)]
+        /// Also rename the markers in the text of its entries
+        rewrite_text: bool,
+    },
+    /// Fold one person, project or tag into another
+    Merge {
+        /// Entity to remove, with its marker (@person, %project or #tag)
+        from: String,
+
+        /// Entity to keep (the marker is optional)
+        into: String,
+
+        #[arg(short, long)]
+        /// Also rewrite the markers in the text of the merged entries
+        rewrite_text: bool,
+    },
+    /// Set details such as status or description on a person, project or tag
+    Set {
+        /// Entity with its marker (@person, %project or #tag)
+        name: String,
+
+        /// Fields as key=value, e.g. status=active; an empty value removes one
+        fields: Vec<String>,
+
+        #[arg(long, conflicts_with = "unarchive")]
+        /// Hide the entity from completion and `dia show`
+        archived: bool,
+
+        #[arg(long)]
+        /// Show an archived entity again
+        unarchive: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum AliasAction {
+    /// Make an alias resolve to a canonical name when logging
+    Add {
+        /// Alias with its marker, e.g. @jk
+        alias: String,
+
+        /// Canonical name (the marker is optional), e.g. @JohnK
+        name: String,
+    },
+    /// List all aliases
+    List,
+    /// Remove an alias
+    Remove {
+        /// Alias with its marker, e.g. @jk
+        alias: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -53,7 +280,7 @@ enum ShowTarget {
     /// Show entries matching filters
     Entries {
         #[arg(short, long)]
-        /// Date or date range (YYYY-MM-DD or YYYY-MM-DD..YYYY-MM-DD)
+        /// Date or period (YYYY-MM-DD, "last week", "Q2 2025", "2025-W14", A..B)
         date: Option<String>,
 
         #[arg(short, long)]
@@ -64,20 +291,135 @@ enum ShowTarget {
         /// Filter by person (@name)
         person: Option<String>,
 
-        #[arg(short, long)]
-        /// Filter by project (%name)
+        #[arg(short = 'P', long)]
+        /// Filter by project (%name), including its sub-projects
         project: Option<String>,
 
         #[arg(short, long)]
-        /// Filter by tag (#name)
+        /// Filter by tag (#name), including its sub-tags
         tag: Option<String>,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
+    /// Dashboard for one person: activity, frequent companions, latest entries
+    Person {
+        /// Name, with or without the @ marker
+        name: String,
+
+        #[arg(short = 'n', long, default_value_t = 5)]
+        /// Number of latest entries to list
+        latest: usize,
+
+        #[arg(long, default_value_t = 5)]
+        /// Number of co-occurring people, projects and tags to list
+        top: usize,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
+    /// Dashboard for one project, including its sub-projects
+    Project {
+        /// Name, with or without the % marker
+        name: String,
+
+        #[arg(short = 'n', long, default_value_t = 5)]
+        /// Number of latest entries to list
+        latest: usize,
+
+        #[arg(long, default_value_t = 5)]
+        /// Number of co-occurring people, projects and tags to list
+        top: usize,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
+    /// Dashboard for one tag, including its sub-tags
+    Tag {
+        /// Name, with or without the # marker
+        name: String,
+
+        #[arg(short = 'n', long, default_value_t = 5)]
+        /// Number of latest entries to list
+        latest: usize,
+
+        #[arg(long, default_value_t = 5)]
+        /// Number of co-occurring people, projects and tags to list
+        top: usize,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
     },
     /// List all people
-    People,
+    People {
+        #[arg(short, long)]
+        /// Include archived people
+        all: bool,
+
+        #[arg(long, value_enum, default_value_t)]
+        /// Sort order
+        sort: show::EntitySort,
+
+        #[arg(long)]
+        /// Only people mentioned on or after this date or period
+        since: Option<String>,
+
+        #[arg(long)]
+        /// Only people not mentioned at all (or not since --since)
+        unused: bool,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
     /// List all projects
-    Projects,
+    Projects {
+        #[arg(short, long)]
+        /// Include archived projects
+        all: bool,
+
+        #[arg(long, value_enum, default_value_t)]
+        /// Sort order
+        sort: show::EntitySort,
+
+        #[arg(long)]
+        /// Only projects mentioned on or after this date or period
+        since: Option<String>,
+
+        #[arg(long)]
+        /// Only projects not mentioned at all (or not since --since)
+        unused: bool,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
     /// List all tags
-    Tags,
+    Tags {
+        #[arg(short, long)]
+        /// Include archived tags
+        all: bool,
+
+        #[arg(long, value_enum, default_value_t)]
+        /// Sort order
+        sort: show::EntitySort,
+
+        #[arg(long)]
+        /// Only tags mentioned on or after this date or period
+        since: Option<String>,
+
+        #[arg(long)]
+        /// Only tags not mentioned at all (or not since --since)
+        unused: bool,
+
+        #[arg(short, long, value_enum, default_value_t)]
+        /// Output format
+        format: OutputFormat,
+    },
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -90,20 +432,222 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             open::that(config.diary_db_path)?;
             println!("Database opened successfully!");
         }
-        Commands::Log { entry, date } => {
-            core.add_entry(&entry, date.as_deref())?;
-            println!("Entry logged successfully!");
+        Commands::Log { entry, date } => match entry {
+            Some(entry) => {
+                core.add_entry(&entry, date.as_deref())?;
+                println!("Entry logged successfully!");
+            }
+            None => completion::log_interactively(&mut core, date.as_deref())?,
+        },
+        Commands::Start { description } => {
+            let timer = timer::start(&core, &description)?;
+            println!("Started: {}", timer.description);
+        }
+        Commands::Stop { discard } => match timer::stop(&mut core, discard)? {
+            Some(entry) => println!("Logged: {entry}"),
+            None => println!("Timer discarded."),
+        },
+        Commands::Pause => {
+            let timer = timer::pause(&core)?;
+            println!("{}", timer.summary(chrono::Utc::now()));
+        }
+        Commands::Resume => {
+            let timer = timer::resume(&core)?;
+            println!("{}", timer.summary(chrono::Utc::now()));
+        }
+        Commands::Status => match timer::current(&core.conn)? {
+            Some(timer) => println!("{}", timer.summary(chrono::Utc::now())),
+            None => println!("No timer running."),
+        },
+        Commands::Edit { id, entry, date } => {
+            let entry = match entry {
+                Some(entry) => entry,
+                None => editor::edit_text(&core.get_entry(id)?.content)?,
+            };
+            if entry.trim().is_empty() {
+                return Err("Entry text cannot be empty; use `dia delete` to remove it".into());
+            }
+            core.update_entry(id, &entry, date.as_deref())?;
+            println!("Entry {id} updated successfully!");
         }
+        Commands::Delete { id, yes } => {
+            let entry = core.get_entry(id)?;
+            if !yes
+                && !confirm(&format!(
+                    "Delete entry {} ({}): \"{}\"?",
+                    id, entry.date, entry.content
+                ))?
+            {
+                println!("Aborted.");
+                return Ok(());
+            }
+            core.delete_entry(id)?;
+            println!("Entry {id} deleted.");
+        }
+        Commands::Entity { action } => match action {
+            EntityAction::Rename {
+                name,
+                new_name,
+                rewrite_text,
+            } => {
+                let (kind, name) = entity_reference(&name)?;
+                let new_name = same_kind(kind, &new_name)?;
+                let rewritten = core.rename_entity(kind, name, new_name, rewrite_text)?;
+                println!(
+                    "Renamed {} to {}.",
+                    core.policy.format_marker(kind, name),
+                    core.policy
+                        .format_marker(kind, &core.policy.normalize(new_name))
+                );
+                if rewrite_text {
+                    println!("Updated the text of {rewritten} entries.");
+                }
+            }
+            EntityAction::Merge {
+                from,
+                into,
+                rewrite_text,
+            } => {
+                let (kind, from) = entity_reference(&from)?;
+                let into = same_kind(kind, &into)?;
+                let rewritten = core.merge_entities(kind, from, into, rewrite_text)?;
+                println!(
+                    "Merged {} into {}.",
+                    core.policy.format_marker(kind, from),
+                    core.policy.format_marker(kind, into)
+                );
+                if rewrite_text {
+                    println!("Updated the text of {rewritten} entries.");
+                }
+            }
+            EntityAction::Set {
+                name,
+                fields,
+                archived,
+                unarchive,
+            } => {
+                let (kind, name) = entity_reference(&name)?;
+                let fields = fields
+                    .iter()
+                    .map(|field| metadata::parse_field(field))
+                    .collect::<anyhow::Result<Vec<_>>>()?;
+                if fields.is_empty() && !archived && !unarchive {
+                    return Err(
+                        "Nothing to set; give key=value fields, --archived or --unarchive".into(),
+                    );
+                }
+                let archive = (archived || unarchive).then_some(archived);
+                metadata::set(&mut core, kind, name, &fields, archive)?;
+                println!("Updated {}.", core.policy.format_marker(kind, name));
+            }
+        },
+        Commands::Alias { action } => match action {
+            AliasAction::Add { alias, name } => {
+                let (kind, alias) = entity_reference(&alias)?;
+                let name = same_kind(kind, &name)?;
+                alias::add(&mut core, kind, alias, name)?;
+                println!(
+                    "{} now resolves to {}.",
+                    core.policy
+                        .format_marker(kind, &core.policy.normalize(alias)),
+                    core.policy
+                        .format_marker(kind, &core.policy.normalize(name))
+                );
+            }
+            AliasAction::List => alias::print_aliases(&core)?,
+            AliasAction::Remove { alias } => {
+                let (kind, name) = entity_reference(&alias)?;
+                if alias::remove(&core, kind, name)? {
+                    println!("Removed alias {alias}.");
+                } else {
+                    return Err(format!("{alias} is not an alias").into());
+                }
+            }
+        },
         Commands::Show { target } => {
             show::handle_show_command(target, &core)?;
         }
-        Commands::Stats { period } => {
-            stats::handle_stats_command(period, &core)?;
+        Commands::Stats {
+            period,
+            chart,
+            top,
+            rollup,
+        } => {
+            stats::handle_stats_command(period, chart, top, rollup, &core)?;
+        }
+        Commands::Report {
+            name,
+            period,
+            rollup,
+            format,
+        } => {
+            report::handle_report_command(&name, period, rollup, format, &core)?;
+        }
+        Commands::Review {
+            project,
+            tag,
+            limit,
+        } => {
+            review::handle_review_command(&mut core, project, tag, limit)?;
+        }
+        Commands::Export {
+            format,
+            from,
+            to,
+            group_by,
+            output,
+        } => {
+            export::handle_export_command(&core, format, from, to, group_by, output)?;
         }
-        Commands::Review => {
-            review::handle_review_command(&core)?;
+        Commands::Import {
+            path,
+            from,
+            links_as_projects,
+            dry_run,
+        } => {
+            import::handle_import_command(&mut core, &path, from, links_as_projects, dry_run)?;
         }
     }
 
     Ok(())
 }
+
+/// Splits `@name`, `%name` or `#name` into the entity kind and name.
+fn entity_reference(reference: &str) -> Result<(EntityKind, &str), String> {
+    EntityKind::parse_reference(reference)
+        .map(|(kind, name)| (kind, unquote(name)))
+        .filter(|(_, name)| !name.is_empty())
+        .ok_or_else(|| format!("'{reference}' needs a marker: @person, %project or #tag"))
+}
+
+/// Strips the marker from a second reference, which must match `kind` if
+/// given.
+fn same_kind(kind: EntityKind, reference: &str) -> Result<&str, String> {
+    match EntityKind::parse_reference(reference) {
+        Some((other, name)) if other == kind => Ok(unquote(name)),
+        Some((other, _)) => Err(format!(
+            "Cannot combine a {} with a {}",
+            kind.noun(),
+            other.noun()
+        )),
+        None => Ok(unquote(reference)),
+    }
+}
+
+/// `"Anna Maria"` as typed in `@"Anna Maria"`.
+fn unquote(name: &str) -> &str {
+    name.strip_prefix('"')
+        .and_then(|quoted| quoted.strip_suffix('"'))
+        .unwrap_or(name)
+}
+
+fn confirm(question: &str) -> std::io::Result<bool> {
+    use std::io::Write;
+
+    print!("{question} [y/N] ");
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
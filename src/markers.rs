@@ -0,0 +1,329 @@
+use crate::entity::EntityKind;
+use serde::{Deserialize, Serialize};
+use unicode_normalization::char::is_combining_mark;
+use unicode_normalization::UnicodeNormalization;
+
+/// How entity names are read from entry text and compared. Configured in the
+/// `[entities]` section of `config.toml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NormalizationPolicy {
+    /// Treat names that differ only in case (`#Bug`, `#bug`) as the same
+    /// entity. The first spelling seen is the one displayed.
+    pub case_folding: bool,
+    /// Store and compare names in Unicode NFC, so composed and decomposed
+    /// accents match.
+    pub unicode_nfc: bool,
+    /// Punctuation allowed inside unquoted names, in addition to letters,
+    /// digits, `_` and `-`. It is never taken from the end of a name, so
+    /// `@J.R.R.` at the end of a sentence reads as `J.R.R`.
+    pub allowed_punctuation: String,
+}
+
+impl Default for NormalizationPolicy {
+    fn default() -> Self {
+        Self {
+            case_folding: true,
+            unicode_nfc: true,
+            allowed_punctuation: ".'".to_string(),
+        }
+    }
+}
+
+/// An entity marker found in entry text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Marker {
+    pub kind: EntityKind,
+    /// The normalized name, without marker or quotes.
+    pub name: String,
+    /// Byte range of the whole marker in the text.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl NormalizationPolicy {
+    /// Cleans up a name for storage: trims it, collapses inner whitespace and
+    /// applies NFC if enabled.
+    pub fn normalize(&self, name: &str) -> String {
+        let collapsed = name.split_whitespace().collect::<Vec<_>>().join(" ");
+        if self.unicode_nfc {
+            collapsed.nfc().collect()
+        } else {
+            collapsed
+        }
+    }
+
+    /// The form names are compared in: normalized, and case-folded if enabled.
+    pub fn key(&self, name: &str) -> String {
+        let name = self.normalize(name);
+        if self.case_folding {
+            name.to_lowercase()
+        } else {
+            name
+        }
+    }
+
+    fn is_name_char(&self, kind: EntityKind, c: char) -> bool {
+        c.is_alphanumeric()
+            || (c == '/' && kind.is_hierarchical())
+            || is_combining_mark(c)
+            || c == '_'
+            || c == '-'
+            || self.allowed_punctuation.contains(c)
+    }
+
+    /// Finds the `@person`, `%project` and `#tag` markers in `text`.
+    ///
+    /// A marker only starts a word: `bob@example.com` and `100%done` contain
+    /// none. URLs, inline code spans and backslash-escaped markers (`\#tag`) are
+    /// skipped. Names run over letters, digits, `_`, `-` and the allowed
+    /// punctuation (plus `/` for projects and tags), or are quoted:
+    /// `@"Anna Maria"`. Trailing punctuation and a possessive `'s` are not part
+    /// of the name.
+    pub fn extract(&self, text: &str) -> Vec<Marker> {
+        let mut markers = Vec::new();
+        let mut pos = 0;
+        let mut prev: Option<char> = None;
+
+        while let Some(c) = text[pos..].chars().next() {
+            let rest = &text[pos..];
+            let word_start = prev.is_none_or(starts_word);
+
+            let skip = match c {
+                '`' => code_span_len(rest),
+                '\\' => 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+                _ if word_start && is_url(rest) => {
+                    rest.find(char::is_whitespace).unwrap_or(rest.len())
+                }
+                '@' | '%' | '#' if word_start => match self.read_marker(text, pos) {
+                    Some(marker) => {
+                        let len = marker.end - pos;
+                        markers.push(marker);
+                        len
+                    }
+                    None => 1,
+                },
+                _ => c.len_utf8(),
+            };
+
+            pos += skip;
+            prev = text[..pos].chars().next_back();
+        }
+
+        markers
+    }
+
+    /// Reads the marker starting at byte `start`, which holds `@`, `%` or `#`.
+    fn read_marker(&self, text: &str, start: usize) -> Option<Marker> {
+        let kind = EntityKind::from_marker(text[start..].chars().next()?)?;
+        let body = &text[start + 1..];
+
+        let (name, end) = if let Some(quoted) = body.strip_prefix('"') {
+            let close = quoted.find(['"', '\n'])?;
+            if !quoted[close..].starts_with('"') {
+                return None;
+            }
+            (&quoted[..close], start + close + 3)
+        } else {
+            let len = body
+                .find(|c| !self.is_name_char(kind, c))
+                .unwrap_or(body.len());
+            let mut name =
+                body[..len].trim_end_matches(|c| c == '/' || self.allowed_punctuation.contains(c));
+            if self.allowed_punctuation.contains('\'') {
+                name = name.strip_suffix("'s").unwrap_or(name);
+            }
+            (name, start + 1 + name.len())
+        };
+
+        let name = self.normalize(name);
+        self.is_valid_name(kind, &name).then_some(Marker {
+            kind,
+            name,
+            start,
+            end,
+        })
+    }
+
+    /// Writes a name back as a marker, quoting it when it could not be read
+    /// back otherwise.
+    pub fn format_marker(&self, kind: EntityKind, name: &str) -> String {
+        let plain = !name.is_empty()
+            && name.chars().all(|c| self.is_name_char(kind, c))
+            && self
+                .extract(&format!("{}{}", kind.marker(), name))
+                .first()
+                .is_some_and(|marker| marker.name == name);
+        if plain {
+            format!("{}{}", kind.marker(), name)
+        } else {
+            format!("{}\"{}\"", kind.marker(), name)
+        }
+    }
+
+    /// Whether `name` can be written as a marker at all. Project and tag
+    /// names must not have empty `/` segments.
+    pub fn is_valid_name(&self, kind: EntityKind, name: &str) -> bool {
+        let name = self.normalize(name);
+        let empty_segment =
+            kind.is_hierarchical() && name.split('/').any(|segment| segment.trim().is_empty());
+        !name.is_empty() && !name.contains('"') && !empty_segment
+    }
+}
+
+/// Whether a marker may start right after `prev`: at whitespace or opening
+/// punctuation, but not inside a word, number or path.
+fn starts_word(prev: char) -> bool {
+    prev.is_whitespace() || "([{<\"'“‘«*~,;:!?".contains(prev)
+}
+
+/// Length of the inline code span at the start of `text`, or of its opening
+/// backticks when the span is never closed.
+fn code_span_len(text: &str) -> usize {
+    let fence = text.len() - text.trim_start_matches('`').len();
+    let mut pos = fence;
+    while let Some(offset) = text[pos..].find('`') {
+        let run_start = pos + offset;
+        let run = text[run_start..].len() - text[run_start..].trim_start_matches('`').len();
+        if run == fence {
+            return run_start + run;
+        }
+        pos = run_start + run;
+    }
+    fence
+}
+
+/// Whether the word at the start of `text` is a URL (`scheme://...` or
+/// `www....`), possibly wrapped in brackets or quotes.
+fn is_url(text: &str) -> bool {
+    let word = text.trim_start_matches(['(', '<', '[', '"', '\'']);
+    if word
+        .get(..4)
+        .is_some_and(|start| start.eq_ignore_ascii_case("www."))
+    {
+        return true;
+    }
+
+    let scheme_len = word
+        .find(|c: char| !(c.is_ascii_alphanumeric() || "+.-".contains(c)))
+        .unwrap_or(word.len());
+    scheme_len > 0
+        && word.starts_with(|c: char| c.is_ascii_alphabetic())
+        && word[scheme_len..].starts_with("://")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `(kind marker, name)` pairs found in `text` with the default policy.
+    fn markers(text: &str) -> Vec<String> {
+        NormalizationPolicy::default()
+            .extract(text)
+            .into_iter()
+            .map(|marker| format!("{}{}", marker.kind.marker(), marker.name))
+            .collect()
+    }
+
+    #[test]
+    fn finds_plain_markers() {
+        assert_eq!(
+            markers("Met @Alice about %Dia #planning"),
+            ["@Alice", "%Dia", "#planning"]
+        );
+        assert_eq!(markers("@Alice,@Bob;#x"), ["@Alice", "@Bob", "#x"]);
+        assert_eq!(
+            markers("(@Alice) [#tag] *#bold* \"@Quoted\""),
+            ["@Alice", "#tag", "#bold", "@Quoted"]
+        );
+    }
+
+    #[test]
+    fn reads_quoted_names() {
+        assert_eq!(
+            markers(r#"Lunch with @"Anna Maria" today"#),
+            ["@Anna Maria"]
+        );
+        assert_eq!(markers("@\"unclosed name"), Vec::<String>::new());
+        assert_eq!(markers("@\"split\nline\" @Bob"), ["@Bob"]);
+    }
+
+    #[test]
+    fn trims_trailing_punctuation_and_possessives() {
+        assert_eq!(markers("Thanks @Alice."), ["@Alice"]);
+        assert_eq!(markers("Read @J.R.R. Tolkien"), ["@J.R.R"]);
+        assert_eq!(
+            markers("@Bob's notes, @O'Brien's desk"),
+            ["@Bob", "@O'Brien"]
+        );
+        assert_eq!(markers("Done with %Dia!"), ["%Dia"]);
+    }
+
+    #[test]
+    fn ignores_markers_inside_words() {
+        assert_eq!(markers("Mail bob@example.com"), Vec::<String>::new());
+        assert_eq!(
+            markers("first.last+dia@mail.example.org"),
+            Vec::<String>::new()
+        );
+        assert_eq!(markers("100%done"), Vec::<String>::new());
+        assert_eq!(markers("Issue C#7 and item#3"), Vec::<String>::new());
+        assert_eq!(markers("Website 50% done, see %Website"), ["%Website"]);
+    }
+
+    #[test]
+    fn skips_urls() {
+        assert_eq!(markers("https://x/#anchor"), Vec::<String>::new());
+        assert_eq!(
+            markers("See <https://example.com/@alice/%20#top> and @Bob"),
+            ["@Bob"]
+        );
+        assert_eq!(markers("(www.example.com/#faq) #docs"), ["#docs"]);
+        assert_eq!(markers("git+ssh://host/%repo"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn skips_code_and_escapes() {
+        assert_eq!(markers("Ran `grep #todo` with @Alice"), ["@Alice"]);
+        assert_eq!(markers("``a ` #inner`` #outer"), ["#outer"]);
+        assert_eq!(markers("Unclosed ` #tag"), ["#tag"]);
+        assert_eq!(markers(r"Ticket \#42, not \@Bob, but #real"), ["#real"]);
+    }
+
+    #[test]
+    fn nests_projects_and_tags_but_not_people() {
+        assert_eq!(
+            markers("On %client/website/frontend. #bug/regression/ @alice/bob"),
+            ["%client/website/frontend", "#bug/regression", "@alice"]
+        );
+        assert_eq!(markers("#a//b and %/root"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn ignores_bare_markers() {
+        assert_eq!(markers("# Heading and ## more"), Vec::<String>::new());
+        assert_eq!(markers("@ % # @."), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_byte_ranges() {
+        let text = "Café with @Zoë.";
+        let found = NormalizationPolicy::default().extract(text);
+        assert_eq!(&text[found[0].start..found[0].end], "@Zoë");
+
+        let text = r#"x @"Anna Maria" y"#;
+        let found = NormalizationPolicy::default().extract(text);
+        assert_eq!(&text[found[0].start..found[0].end], r#"@"Anna Maria""#);
+    }
+
+    #[test]
+    fn quotes_names_that_need_it() {
+        let policy = NormalizationPolicy::default();
+        assert_eq!(policy.format_marker(EntityKind::Person, "Alice"), "@Alice");
+        assert_eq!(
+            policy.format_marker(EntityKind::Person, "Anna Maria"),
+            "@\"Anna Maria\""
+        );
+        assert_eq!(policy.format_marker(EntityKind::Project, "v2."), "%\"v2.\"");
+    }
+}
@@ -0,0 +1,132 @@
+use crate::core::Core;
+use crate::entity::EntityKind;
+use anyhow::{anyhow, Result};
+use rusqlite::{params, Connection, Transaction};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Details attached to a person, project or tag with `dia entity set`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// Archived entities are left out of completion and `dia show`.
+    #[serde(default)]
+    pub archived: bool,
+    /// Free-form fields such as `status` or `description`.
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+}
+
+impl Metadata {
+    /// One-line rendering, e.g. `client: ACME, status: done, archived`.
+    pub fn summary(&self) -> String {
+        let mut parts = self
+            .fields
+            .iter()
+            .map(|(key, value)| format!("{key}: {value}"))
+            .collect::<Vec<_>>();
+        if self.archived {
+            parts.push("archived".to_string());
+        }
+        parts.join(", ")
+    }
+}
+
+/// The metadata of every entity of one kind that has any, keyed by name.
+pub fn load(conn: &Connection, kind: EntityKind) -> rusqlite::Result<BTreeMap<String, Metadata>> {
+    let mut metadata: BTreeMap<String, Metadata> = BTreeMap::new();
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT name FROM {} WHERE archived ORDER BY name",
+        kind.table()
+    ))?;
+    for name in stmt.query_map([], |row| row.get::<_, String>(0))? {
+        metadata.entry(name?).or_default().archived = true;
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT x.name, m.key, m.value FROM entity_metadata m
+         JOIN {} x ON x.id = m.entity_id
+         WHERE m.kind = ? ORDER BY x.name, m.key",
+        kind.table()
+    ))?;
+    let rows = stmt.query_map(params![kind.noun()], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+        ))
+    })?;
+    for row in rows {
+        let (name, key, value) = row?;
+        metadata.entry(name).or_default().fields.insert(key, value);
+    }
+
+    Ok(metadata)
+}
+
+/// Splits a `key=value` argument. Keys are letters, digits, `_` and `-`.
+pub fn parse_field(field: &str) -> Result<(String, String)> {
+    let (key, value) = field
+        .split_once('=')
+        .ok_or_else(|| anyhow!("Expected key=value, got '{}'", field))?;
+    let key = key.trim();
+    if key.is_empty()
+        || !key
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(anyhow!(
+            "'{}' is not a valid field name; use letters, digits, '_' and '-'",
+            key
+        ));
+    }
+    Ok((key.to_lowercase(), value.trim().to_string()))
+}
+
+/// Sets fields on an existing entity (an empty value removes the field) and,
+/// if `archived` is given, archives or restores it.
+pub fn set(
+    core: &mut Core,
+    kind: EntityKind,
+    name: &str,
+    fields: &[(String, String)],
+    archived: Option<bool>,
+) -> Result<()> {
+    let tx = core.conn.transaction()?;
+    let id = Core::require_entity(&tx, kind, name)?;
+    store(&tx, kind, id, fields, archived)?;
+    tx.commit()?;
+    Ok(())
+}
+
+/// Writes fields and the archived flag of the entity with `id`.
+pub fn store(
+    tx: &Transaction,
+    kind: EntityKind,
+    id: i64,
+    fields: &[(String, String)],
+    archived: Option<bool>,
+) -> rusqlite::Result<()> {
+    for (key, value) in fields {
+        if value.is_empty() {
+            tx.execute(
+                "DELETE FROM entity_metadata WHERE kind = ? AND entity_id = ? AND key = ?",
+                params![kind.noun(), id, key],
+            )?;
+        } else {
+            tx.execute(
+                "INSERT OR REPLACE INTO entity_metadata (kind, entity_id, key, value)
+                 VALUES (?, ?, ?, ?)",
+                params![kind.noun(), id, key, value],
+            )?;
+        }
+    }
+
+    if let Some(archived) = archived {
+        tx.execute(
+            &format!("UPDATE {} SET archived = ? WHERE id = ?", kind.table()),
+            params![archived, id],
+        )?;
+    }
+    Ok(())
+}
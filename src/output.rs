@@ -0,0 +1,62 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// How `show` commands print their results.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable table
+    #[default]
+    Table,
+    /// A single JSON array
+    Json,
+    /// One JSON object per line
+    Jsonl,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+/// Records that can be flattened into CSV rows.
+pub trait CsvRecord {
+    const HEADERS: &'static [&'static str];
+
+    fn csv_row(&self) -> Vec<String>;
+}
+
+/// Writes records to stdout in one of the machine-readable formats. Table
+/// output is left to the caller.
+pub fn write_records<T: Serialize + CsvRecord>(
+    records: &[T],
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let mut out = io::stdout().lock();
+
+    match format {
+        OutputFormat::Table => {}
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, records)?;
+            writeln!(out)?;
+        }
+        OutputFormat::Jsonl => {
+            for record in records {
+                serde_json::to_writer(&mut out, record)?;
+                writeln!(out)?;
+            }
+        }
+        OutputFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(out);
+            writer.write_record(T::HEADERS)?;
+            for record in records {
+                writer.write_record(record.csv_row())?;
+            }
+            writer.flush()?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Joins a list column into a single CSV cell.
+pub fn join_list(values: &[String]) -> String {
+    values.join(";")
+}
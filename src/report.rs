@@ -0,0 +1,452 @@
+use crate::config::Config;
+use crate::core::Core;
+use crate::dates;
+use crate::duration::{self, format_minutes};
+use crate::entity::{EntityKind, EntryWithRelations};
+use crate::filter::EntryFilter;
+use crate::stats::{self, Measure};
+use crate::template::Template;
+use anyhow::{anyhow, Context};
+use chrono::{Duration, Local, NaiveDate};
+use clap::ValueEnum;
+use comfy_table::Table;
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Built-in reports; templates in `~/.config/dia/templates` add more.
+const REPORTS: [&str; 4] = ["time", "standup", "weekly", "brag"];
+
+/// Period reported on when `--period` is not given.
+const DEFAULT_PERIOD: &str = "this week";
+
+/// Period of the brag document when `--period` is not given.
+const BRAG_PERIOD: &str = "this year";
+
+/// Group of entries that mention no project.
+const NO_PROJECT: &str = "No project";
+
+/// Output of the templated reports. User templates may have any extension;
+/// without `--format`, `.md` is preferred, then `.txt`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ReportFormat {
+    /// Markdown, from `<name>.md`
+    #[default]
+    Markdown,
+    /// Plain text, from `<name>.txt`
+    Text,
+}
+
+impl ReportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Text => "txt",
+        }
+    }
+}
+
+pub fn handle_report_command(
+    name: &str,
+    period: Option<String>,
+    rollup: bool,
+    format: Option<ReportFormat>,
+    core: &Core,
+) -> anyhow::Result<()> {
+    let today = Local::now().date_naive();
+    if name == "time" {
+        let period = period.as_deref().unwrap_or(DEFAULT_PERIOD);
+        return time_report(core, dates::parse_span(period, today)?, rollup);
+    }
+
+    let path = find_template(name, format)?.ok_or_else(|| {
+        let mut available = REPORTS
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        for template in user_templates().unwrap_or_default() {
+            if !available.contains(&template) {
+                available.push(template);
+            }
+        }
+        let dir = templates_dir().map_or_else(
+            |_| "the templates directory".into(),
+            |dir| dir.display().to_string(),
+        );
+        match format {
+            Some(format) if available.iter().any(|report| report == name) => anyhow!(
+                "There is no {}.{} template in {}",
+                name,
+                format.extension(),
+                dir
+            ),
+            _ => anyhow!(
+                "Unknown report '{}'; available reports: {}. Add your own as a template in {}",
+                name,
+                available.join(", "),
+                dir
+            ),
+        }
+    })?;
+
+    let span = |default: &str| -> anyhow::Result<EntryFilter> {
+        let period = period.as_deref().unwrap_or(default);
+        Ok(EntryFilter {
+            dates: Some(dates::parse_span(period, today)?),
+            ..Default::default()
+        })
+    };
+    let context = match name {
+        "standup" => {
+            if period.is_some() {
+                return Err(anyhow!("The standup always covers yesterday and today"));
+            }
+            let yesterday = today - Duration::days(1);
+            let filter = EntryFilter {
+                dates: Some((yesterday, today)),
+                ..Default::default()
+            };
+            let sections = [("Yesterday", yesterday), ("Today", today)];
+            context(core, name, today, filter, &sections)?
+        }
+        "brag" => {
+            let tags = Config::load()?.reports.achievement_tags;
+            if tags.is_empty() {
+                return Err(anyhow!(
+                    "No achievement tags configured; set achievement_tags in the [reports] \
+                     section of config.toml"
+                ));
+            }
+            let filter = EntryFilter {
+                any_tag: tags.clone(),
+                ..span(BRAG_PERIOD)?
+            };
+            let mut context = context(core, name, today, filter, &[])?;
+            context["achievement_tags"] = json!(tags);
+            context
+        }
+        _ => context(core, name, today, span(DEFAULT_PERIOD)?, &[])?,
+    };
+    render(&path, &context)
+}
+
+/// Time spent per project, person and tag, followed by the timed entries.
+fn time_report(
+    core: &Core,
+    (from, to): (NaiveDate, NaiveDate),
+    rollup: bool,
+) -> anyhow::Result<()> {
+    let filter = &EntryFilter {
+        dates: Some((from, to)),
+        ..Default::default()
+    };
+    let total = stats::tracked_minutes(core, filter)?;
+    if total == 0 {
+        println!("No time tracked from {} to {}.", from, to);
+        return Ok(());
+    }
+
+    println!("Time from {} to {}: {}", from, to, format_minutes(total));
+    println!();
+    for kind in [EntityKind::Project, EntityKind::Person, EntityKind::Tag] {
+        let times = stats::entity_totals(core, filter, kind, rollup, Measure::Minutes)?;
+        stats::print_time(kind.noun(), &times, total, false);
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec!["Date", "Time", "Entry"]);
+    for entry in filter.entries(&core.conn)? {
+        if let Some(minutes) = entry.minutes.filter(|minutes| *minutes > 0) {
+            table.add_row(vec![
+                entry.date.to_string(),
+                format_minutes(minutes),
+                entry.content,
+            ]);
+        }
+    }
+    stats::right_align(&mut table, 1);
+    println!("{table}");
+
+    Ok(())
+}
+
+/// Everything a report template can use: the entries matching `filter`
+/// with totals, grouped by project and by day, and the same for each of the
+/// single-day `sections`.
+fn context(
+    core: &Core,
+    report: &str,
+    today: NaiveDate,
+    filter: EntryFilter,
+    sections: &[(&str, NaiveDate)],
+) -> anyhow::Result<Value> {
+    let (from, to) = filter.dates.unwrap_or((today, today));
+    let items = EntryWithRelations::from_entries(&core.conn, filter.entries(&core.conn)?)?
+        .into_iter()
+        .map(|entry| Item::new(core, entry))
+        .collect::<Vec<_>>();
+    let items = items.iter().collect::<Vec<_>>();
+
+    let mut context = group(&items);
+    context["report"] = json!(report);
+    context["today"] = json!(today.to_string());
+    context["from"] = json!(from.to_string());
+    context["to"] = json!(to.to_string());
+    context["sections"] = sections
+        .iter()
+        .map(|(title, date)| {
+            let day = date.to_string();
+            let items = items
+                .iter()
+                .copied()
+                .filter(|item| item.entry.date == day)
+                .collect::<Vec<_>>();
+            let mut section = group(&items);
+            section["title"] = json!(title);
+            section["date"] = json!(day);
+            section
+        })
+        .collect();
+    Ok(context)
+}
+
+/// An entry along with how templates see it.
+struct Item {
+    entry: EntryWithRelations,
+    value: Value,
+}
+
+impl Item {
+    fn new(core: &Core, entry: EntryWithRelations) -> Self {
+        let weekday = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d")
+            .map(|date| date.format("%a").to_string())
+            .unwrap_or_default();
+        let minutes = entry.minutes.unwrap_or(0);
+        let value = json!({
+            "id": entry.id,
+            "date": entry.date,
+            "weekday": weekday,
+            "content": entry.content,
+            "text": plain_text(core, &entry.content),
+            "people": entry.people,
+            "projects": entry.projects,
+            "tags": entry.tags,
+            "minutes": minutes,
+            "time": time(minutes),
+        });
+        Self { entry, value }
+    }
+}
+
+/// The entry text with markers replaced by the names they mention, without
+/// durations and without the tags it ends with, so
+/// `Paired with @"Anna Maria" on %Dia ~1h #win` reads
+/// `Paired with Anna Maria on Dia`.
+fn plain_text(core: &Core, content: &str) -> String {
+    let mut markers = core.policy.extract(content);
+    // Tags at the end label the entry rather than being part of a sentence.
+    let mut end = content.len();
+    while let Some(marker) = markers.last() {
+        let after = duration::strip(&content[marker.end..end]);
+        if marker.kind != EntityKind::Tag || !after.trim().is_empty() {
+            break;
+        }
+        end = marker.start;
+        markers.pop();
+    }
+
+    let mut text = String::new();
+    let mut last = 0;
+    for marker in markers {
+        text.push_str(&content[last..marker.start]);
+        text.push_str(&marker.name);
+        last = marker.end;
+    }
+    text.push_str(&content[last..end]);
+    duration::strip(&text)
+}
+
+/// The entries with their totals, their projects (entries without a project
+/// last) and their days.
+fn group(items: &[&Item]) -> Value {
+    let mut value = summary(items);
+    value["projects"] = projects(items);
+    value["days"] = days(items, true);
+    value
+}
+
+/// The entries, how many there are, the dates of the first and last, the
+/// people and tags they mention and the time noted in them.
+fn summary(items: &[&Item]) -> Value {
+    let names = |names: fn(&EntryWithRelations) -> &Vec<String>| {
+        let mut names = items
+            .iter()
+            .flat_map(|item| names(&item.entry).iter().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        names.sort_by_key(|name| name.to_lowercase());
+        names
+    };
+    let minutes = items
+        .iter()
+        .filter_map(|item| item.entry.minutes)
+        .sum::<i64>();
+    json!({
+        "entries": items.iter().map(|item| &item.value).collect::<Vec<_>>(),
+        "count": items.len(),
+        "first": items.first().map(|item| &item.entry.date),
+        "last": items.last().map(|item| &item.entry.date),
+        "people": names(|entry| &entry.people),
+        "tags": names(|entry| &entry.tags),
+        "minutes": minutes,
+        "time": time(minutes),
+    })
+}
+
+fn projects(items: &[&Item]) -> Value {
+    let mut projects: BTreeMap<&str, Vec<&Item>> = BTreeMap::new();
+    let mut unassigned = Vec::new();
+    for item in items {
+        if item.entry.projects.is_empty() {
+            unassigned.push(*item);
+        }
+        for project in &item.entry.projects {
+            projects.entry(project).or_default().push(item);
+        }
+    }
+
+    let mut groups = projects.into_iter().collect::<Vec<_>>();
+    if !unassigned.is_empty() {
+        groups.push((NO_PROJECT, unassigned));
+    }
+    groups
+        .into_iter()
+        .map(|(name, items)| {
+            let mut project = summary(&items);
+            project["name"] = json!(name);
+            project["days"] = days(&items, false);
+            project
+        })
+        .collect()
+}
+
+/// One group per day with entries, in order; `with_projects` also groups
+/// each day's entries by project.
+fn days(items: &[&Item], with_projects: bool) -> Value {
+    let mut days: BTreeMap<&str, Vec<&Item>> = BTreeMap::new();
+    for item in items {
+        days.entry(&item.entry.date).or_default().push(item);
+    }
+    days.into_iter()
+        .map(|(date, items)| {
+            let mut day = summary(&items);
+            day["date"] = json!(date);
+            day["weekday"] = items[0].value["weekday"].clone();
+            if with_projects {
+                day["projects"] = projects(&items);
+            }
+            day
+        })
+        .collect()
+}
+
+/// `1h 30m`, or nothing when no time was noted.
+fn time(minutes: i64) -> String {
+    if minutes > 0 {
+        format_minutes(minutes)
+    } else {
+        String::new()
+    }
+}
+
+fn templates_dir() -> anyhow::Result<PathBuf> {
+    Ok(Config::dir()?.join("templates"))
+}
+
+/// Names of the templates in the templates directory.
+fn user_templates() -> anyhow::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in fs::read_dir(templates_dir()?)? {
+        let path = entry?.path();
+        if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+            if path.is_file() && !stem.starts_with('.') {
+                names.push(stem.to_string());
+            }
+        }
+    }
+    names.sort();
+    names.dedup();
+    Ok(names)
+}
+
+/// Finds the template for a report. A built-in template is written to the
+/// templates directory on first use, unless the user already has a template
+/// of that name, so it can be edited there.
+fn find_template(name: &str, format: Option<ReportFormat>) -> anyhow::Result<Option<PathBuf>> {
+    if name.is_empty() || name.starts_with('.') || name.contains(['/', '\\']) {
+        return Err(anyhow!("'{}' is not a report name", name));
+    }
+    let dir = templates_dir()?;
+
+    let candidates = match format {
+        Some(format) => vec![format!("{}.{}", name, format.extension())],
+        None => {
+            let mut others = Vec::new();
+            if dir.is_dir() {
+                for entry in fs::read_dir(&dir)? {
+                    let path = entry?.path();
+                    if path.file_stem().and_then(|stem| stem.to_str()) == Some(name) {
+                        if let Some(file_name) = path.file_name().and_then(|f| f.to_str()) {
+                            others.push(file_name.to_string());
+                        }
+                    }
+                }
+            }
+            others.sort();
+            let mut candidates = vec![format!("{name}.md"), format!("{name}.txt")];
+            candidates.extend(others);
+            candidates
+        }
+    };
+    if let Some(path) = candidates
+        .into_iter()
+        .map(|file_name| dir.join(file_name))
+        .find(|path| path.is_file())
+    {
+        return Ok(Some(path));
+    }
+
+    let format = format.unwrap_or_default();
+    let Some(default) = default_template(name, format) else {
+        return Ok(None);
+    };
+    let path = dir.join(format!("{}.{}", name, format.extension()));
+    fs::create_dir_all(&dir)?;
+    fs::write(&path, default)?;
+    Ok(Some(path))
+}
+
+fn render(path: &Path, context: &Value) -> anyhow::Result<()> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Could not read template {}", path.display()))?;
+    let template =
+        Template::parse(&source).with_context(|| format!("Invalid template {}", path.display()))?;
+    let output = template
+        .render(context)
+        .with_context(|| format!("Could not render template {}", path.display()))?;
+    println!("{}", output.trim_end());
+    Ok(())
+}
+
+fn default_template(name: &str, format: ReportFormat) -> Option<&'static str> {
+    match (name, format) {
+        ("standup", ReportFormat::Markdown) => Some(include_str!("templates/standup.md")),
+        ("standup", ReportFormat::Text) => Some(include_str!("templates/standup.txt")),
+        ("weekly", ReportFormat::Markdown) => Some(include_str!("templates/weekly.md")),
+        ("weekly", ReportFormat::Text) => Some(include_str!("templates/weekly.txt")),
+        ("brag", ReportFormat::Markdown) => Some(include_str!("templates/brag.md")),
+        _ => None,
+    }
+}
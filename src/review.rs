@@ -1,6 +1,182 @@
-use crate::core::Core;
+use crate::core::{Core, Entry};
+use crate::filter::EntryFilter;
+use chrono::{Duration, Local, NaiveDate};
+use rusqlite::{params, params_from_iter};
+use std::io::{self, BufRead, Write};
 
-pub fn handle_review_command(_core: &Core) -> Result<(), Box<dyn std::error::Error>> {
-    println!("Spaced repetition review coming soon!");
+/// SM-2 scheduling state of a single entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Schedule {
+    pub ease: f64,
+    pub interval_days: i64,
+    pub repetitions: i64,
+}
+
+impl Schedule {
+    /// Applies an SM-2 recall grade, from 0 (complete blackout) to 5 (perfect
+    /// recall). Grades below 3 restart the repetition sequence.
+    pub fn grade(self, quality: u8) -> Self {
+        let quality = quality.min(5);
+        let q = f64::from(quality);
+        let ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(1.3);
+
+        if quality < 3 {
+            return Self {
+                ease,
+                interval_days: 1,
+                repetitions: 0,
+            };
+        }
+
+        let repetitions = self.repetitions + 1;
+        let interval_days = match repetitions {
+            1 => 1,
+            2 => 6,
+            _ => (self.interval_days as f64 * self.ease).round() as i64,
+        };
+
+        Self {
+            ease,
+            interval_days,
+            repetitions,
+        }
+    }
+}
+
+pub fn handle_review_command(
+    core: &mut Core,
+    project: Option<String>,
+    tag: Option<String>,
+    limit: usize,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let today = Local::now().date_naive();
+    let filter = EntryFilter {
+        project,
+        tag,
+        ..Default::default()
+    };
+    let due = due_entries(core, &filter, today, limit)?;
+
+    if due.is_empty() {
+        println!("No entries due for review.");
+        return Ok(());
+    }
+
+    println!(
+        "{} entries due for review. Grade your recall from 0 (forgot) to 5 (perfect), \
+         's' to skip, 'q' to quit.",
+        due.len()
+    );
+
+    let mut lines = io::stdin().lock().lines();
+    let mut reviewed = 0;
+
+    'session: for (index, (entry, schedule)) in due.iter().enumerate() {
+        println!();
+        println!(
+            "[{}/{}] {} (#{})",
+            index + 1,
+            due.len(),
+            entry.date,
+            entry.id
+        );
+        println!("{}", entry.content);
+
+        let quality = loop {
+            print!("Grade [0-5/s/q]: ");
+            io::stdout().flush()?;
+
+            let Some(line) = lines.next().transpose()? else {
+                println!();
+                break 'session;
+            };
+            match line.trim() {
+                "q" => break 'session,
+                "s" => continue 'session,
+                answer => match answer.parse::<u8>() {
+                    Ok(quality) if quality <= 5 => break quality,
+                    _ => println!("Please enter a number from 0 to 5, 's' or 'q'."),
+                },
+            }
+        };
+
+        let next = schedule.grade(quality);
+        save_schedule(core, entry.id, next, today)?;
+        reviewed += 1;
+        println!("Next review in {} day(s).", next.interval_days);
+    }
+
+    println!();
+    println!("Reviewed {} of {} entries.", reviewed, due.len());
     Ok(())
 }
+
+/// Entries whose next review is on or before `today`, oldest first. Entries
+/// that were never reviewed are due from the day they were logged.
+fn due_entries(
+    core: &Core,
+    filter: &EntryFilter,
+    today: NaiveDate,
+    limit: usize,
+) -> rusqlite::Result<Vec<(Entry, Schedule)>> {
+    let (mut conditions, mut params) = filter.conditions(&core.conn)?;
+    conditions.push("COALESCE(r.due_date, e.date) <= ?".to_string());
+    params.push(today.to_string());
+
+    let query = format!(
+        "SELECT {}, COALESCE(r.ease, 2.5), COALESCE(r.interval_days, 0), COALESCE(r.repetitions, 0)
+         FROM entries e LEFT JOIN review_state r ON r.entry_id = e.id
+         WHERE {}
+         ORDER BY COALESCE(r.due_date, e.date), e.id
+         LIMIT {}",
+        Entry::COLUMNS,
+        conditions.join(" AND "),
+        limit
+    );
+
+    let mut stmt = core.conn.prepare(&query)?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        Ok((
+            Entry::from_row(row)?,
+            Schedule {
+                ease: row.get(5)?,
+                interval_days: row.get(6)?,
+                repetitions: row.get(7)?,
+            },
+        ))
+    })?;
+    rows.collect()
+}
+
+fn save_schedule(
+    core: &mut Core,
+    entry_id: i64,
+    schedule: Schedule,
+    today: NaiveDate,
+) -> rusqlite::Result<()> {
+    let tx = core.conn.transaction()?;
+    let due_date = today + Duration::days(schedule.interval_days);
+
+    tx.execute(
+        "INSERT INTO review_state (entry_id, ease, interval_days, repetitions, due_date)
+         VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT (entry_id) DO UPDATE SET
+             ease = excluded.ease,
+             interval_days = excluded.interval_days,
+             repetitions = excluded.repetitions,
+             due_date = excluded.due_date",
+        params![
+            entry_id,
+            schedule.ease,
+            schedule.interval_days,
+            schedule.repetitions,
+            due_date.to_string()
+        ],
+    )?;
+    tx.execute(
+        "UPDATE entries SET last_reviewed = ? WHERE id = ?",
+        params![today.to_string(), entry_id],
+    )?;
+
+    tx.commit()
+}
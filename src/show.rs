@@ -1,5 +1,26 @@
-use crate::core::Core;
+use crate::core::{Core, Entry};
+use crate::entity::{leaf_name, Entity, EntityKind, EntryWithRelations};
+use crate::filter::EntryFilter;
+use crate::metadata::{self, Metadata};
+use crate::output::{self, OutputFormat};
+use crate::{dashboard, dates, stats};
+use chrono::Local;
+use clap::ValueEnum;
 use comfy_table::Table;
+use rusqlite::params_from_iter;
+use std::collections::{HashMap, HashSet};
+
+/// Order of `dia show people|projects|tags`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum EntitySort {
+    /// Alphabetical, with nested projects and tags drawn as a tree
+    #[default]
+    Name,
+    /// Most entries first
+    Count,
+    /// Most recently used first
+    Recent,
+}
 
 pub fn handle_show_command(target: crate::ShowTarget, core: &Core) -> anyhow::Result<()> {
     match target {
@@ -9,10 +30,47 @@ pub fn handle_show_command(target: crate::ShowTarget, core: &Core) -> anyhow::Re
             person,
             project,
             tag,
-        } => show_entries(core, date, search, person, project, tag),
-        crate::ShowTarget::People => show_entities(core, "people"),
-        crate::ShowTarget::Projects => show_entities(core, "projects"),
-        crate::ShowTarget::Tags => show_entities(core, "tags"),
+            format,
+        } => show_entries(core, date, search, person, project, tag, format),
+        crate::ShowTarget::People {
+            all,
+            sort,
+            since,
+            unused,
+            format,
+        } => show_entities(core, EntityKind::Person, all, sort, since, unused, format),
+        crate::ShowTarget::Projects {
+            all,
+            sort,
+            since,
+            unused,
+            format,
+        } => show_entities(core, EntityKind::Project, all, sort, since, unused, format),
+        crate::ShowTarget::Tags {
+            all,
+            sort,
+            since,
+            unused,
+            format,
+        } => show_entities(core, EntityKind::Tag, all, sort, since, unused, format),
+        crate::ShowTarget::Person {
+            name,
+            latest,
+            top,
+            format,
+        } => dashboard::show_dashboard(core, EntityKind::Person, &name, latest, top, format),
+        crate::ShowTarget::Project {
+            name,
+            latest,
+            top,
+            format,
+        } => dashboard::show_dashboard(core, EntityKind::Project, &name, latest, top, format),
+        crate::ShowTarget::Tag {
+            name,
+            latest,
+            top,
+            format,
+        } => dashboard::show_dashboard(core, EntityKind::Tag, &name, latest, top, format),
     }
 }
 
@@ -23,19 +81,29 @@ fn show_entries(
     person: Option<String>,
     project: Option<String>,
     tag: Option<String>,
+    format: OutputFormat,
 ) -> anyhow::Result<()> {
     // Get entries from database
     let entries = get_filtered_entries(core, date_filter, search_term, person, project, tag)?;
 
+    if format != OutputFormat::Table {
+        let entries = EntryWithRelations::from_entries(&core.conn, entries)?;
+        return output::write_records(&entries, format);
+    }
+
     // Display entries
     let mut table = Table::new();
-    table.set_header(vec!["Date", "Entry"]);
+    table.set_header(vec!["ID", "Date", "Entry"]);
 
     if entries.is_empty() {
         println!("No entries found matching your criteria.");
     } else {
         for entry in entries {
-            table.add_row(vec![entry.date.to_string(), entry.content]);
+            table.add_row(vec![
+                entry.id.to_string(),
+                entry.date.to_string(),
+                entry.content,
+            ]);
         }
         println!("{table}");
     }
@@ -44,53 +112,200 @@ fn show_entries(
 }
 
 fn get_filtered_entries(
-    _core: &Core,
-    _date_filter: Option<String>,
-    _search_term: Option<String>,
-    _person: Option<String>,
-    _project: Option<String>,
-    _tag: Option<String>,
-) -> anyhow::Result<Vec<crate::core::Entry>> {
-    // This is a placeholder - in a real implementation, we would:
-    // 1. Build a SQL query based on the filters
-    // 2. Execute the query against the database
-    // 3. Return the results as Entry structs
-
-    // For now, return a dummy entry to use the Entry struct
-    let entry = crate::core::Entry {
-        id: 1,
-        content: "Example entry with @John and %Project".to_string(),
-        date: chrono::NaiveDate::from_ymd_opt(2025, 3, 1).unwrap(),
-        created_at: chrono::Local::now(),
+    core: &Core,
+    date_filter: Option<String>,
+    search_term: Option<String>,
+    person: Option<String>,
+    project: Option<String>,
+    tag: Option<String>,
+) -> anyhow::Result<Vec<Entry>> {
+    let filter = EntryFilter {
+        dates: date_filter
+            .map(|d| dates::parse_span(&d, Local::now().date_naive()))
+            .transpose()?,
+        search: search_term,
+        person,
+        project,
+        tag,
+        ..Default::default()
     };
-    println!("Entry ID: {}, Created at: {}", entry.id, entry.created_at);
 
-    Ok(vec![entry])
+    Ok(filter.entries(&core.conn)?)
 }
 
-fn show_entities(core: &Core, entity_type: &str) -> anyhow::Result<()> {
-    let mut table = Table::new();
-    table.set_header(vec![entity_type]);
+fn show_entities(
+    core: &Core,
+    kind: EntityKind,
+    all: bool,
+    sort: EntitySort,
+    since: Option<String>,
+    unused: bool,
+    format: OutputFormat,
+) -> anyhow::Result<()> {
+    let (entity_table, link_table, id_column) = (kind.table(), kind.link_table(), kind.id_column());
+    let parent_column = if kind.is_hierarchical() {
+        "x.parent_id"
+    } else {
+        "NULL"
+    };
+    let mut conditions = Vec::new();
+    if !all {
+        conditions.push("NOT x.archived".to_string());
+    }
+    if unused && kind.is_hierarchical() {
+        // Parents exist for their children's sake; they are never stale.
+        conditions.push(format!(
+            "x.id NOT IN (SELECT parent_id FROM {entity_table} WHERE parent_id IS NOT NULL)"
+        ));
+    }
+    let where_clause = if conditions.is_empty() {
+        String::new()
+    } else {
+        format!(" WHERE {}", conditions.join(" AND "))
+    };
+
+    let since = since
+        .map(|since| dates::parse_span(&since, Local::now().date_naive()))
+        .transpose()?
+        .map(|(start, _)| start.to_string());
+    let having = match (&since, unused) {
+        (None, false) => "",
+        (None, true) => " HAVING COUNT(e.id) = 0",
+        (Some(_), false) => " HAVING MAX(e.date) >= ?",
+        (Some(_), true) => " HAVING MAX(e.date) IS NULL OR MAX(e.date) < ?",
+    };
+    let order = match sort {
+        EntitySort::Name => "x.name",
+        EntitySort::Count => "COUNT(e.id) DESC, x.name",
+        EntitySort::Recent => "MAX(e.date) IS NULL, MAX(e.date) DESC, x.name",
+    };
+
+    let mut stmt = core.conn.prepare(&format!(
+        "SELECT x.id, x.name, COUNT(e.id), MIN(e.date), MAX(e.date), x.archived, {parent_column}
+         FROM {entity_table} x
+         LEFT JOIN {link_table} l ON l.{id_column} = x.id
+         LEFT JOIN entries e ON e.id = l.entry_id{where_clause}
+         GROUP BY x.id{having} ORDER BY {order}"
+    ))?;
+    let mut metadata = metadata::load(&core.conn, kind)?;
+    let (entities, parents): (Vec<_>, Vec<_>) = stmt
+        .query_map(params_from_iter(since.iter()), |row| {
+            Ok((
+                Entity {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    entries: row.get(2)?,
+                    first_seen: row.get(3)?,
+                    last_used: row.get(4)?,
+                    archived: row.get(5)?,
+                    metadata: Default::default(),
+                },
+                row.get::<_, Option<i64>>(6)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .map(|(mut entity, parent)| {
+            if let Some(details) = metadata.remove(&entity.name) {
+                entity.metadata = details.fields;
+            }
+            (entity, parent)
+        })
+        .unzip();
+
+    if format != OutputFormat::Table {
+        return output::write_records(&entities, format);
+    }
 
-    let query = match entity_type {
-        "people" => "SELECT name FROM people ORDER BY name",
-        "projects" => "SELECT name FROM projects ORDER BY name",
-        "tags" => "SELECT name FROM tags ORDER BY name",
-        _ => return Err(anyhow::anyhow!("Invalid entity type")),
+    let details = |entity: &Entity| {
+        Metadata {
+            archived: entity.archived,
+            fields: entity.metadata.clone(),
+        }
+        .summary()
     };
+    let with_details = entities
+        .iter()
+        .any(|entity| entity.archived || !entity.metadata.is_empty());
 
-    let mut stmt = core.conn.prepare(query)?;
-    let entities = stmt.query_map([], |row| row.get::<_, String>(0))?;
+    // Only alphabetical listings are drawn as a tree.
+    let lines = if sort == EntitySort::Name {
+        tree_lines(&entities, &parents)
+    } else {
+        entities
+            .iter()
+            .map(|entity| (entity.name.clone(), entity))
+            .collect()
+    };
+
+    let mut table = Table::new();
+    let mut header = vec![entity_table, "entries", "first seen", "last used"];
+    if with_details {
+        header.push("details");
+    }
+    table.set_header(header);
 
-    for entity in entities {
-        table.add_row(vec![entity?]);
+    for (line, entity) in lines {
+        let mut row = vec![
+            line,
+            entity.entries.to_string(),
+            entity.first_seen.clone().unwrap_or_default(),
+            entity.last_used.clone().unwrap_or_default(),
+        ];
+        if with_details {
+            row.push(details(entity));
+        }
+        table.add_row(row);
     }
+    stats::right_align(&mut table, 1);
 
     if table.row_iter().count() == 0 {
-        println!("No {} found.", entity_type);
+        println!("No {} found.", entity_table);
     } else {
         println!("{table}");
     }
 
     Ok(())
 }
+
+/// Draws nested projects and tags as a tree, one line per entity, children
+/// under their parents and labelled with the last part of their name.
+/// `entities` are in display order; `parents` holds each one's parent id.
+fn tree_lines<'a>(entities: &'a [Entity], parents: &[Option<i64>]) -> Vec<(String, &'a Entity)> {
+    let ids = entities
+        .iter()
+        .map(|entity| entity.id)
+        .collect::<HashSet<_>>();
+    let mut children: HashMap<Option<i64>, Vec<&Entity>> = HashMap::new();
+    for (entity, parent) in entities.iter().zip(parents) {
+        // Entities whose parent is missing are shown as roots.
+        let parent = parent.filter(|id| ids.contains(id));
+        children.entry(parent).or_default().push(entity);
+    }
+
+    let mut lines = Vec::new();
+    for root in children.get(&None).into_iter().flatten() {
+        lines.push((root.name.clone(), *root));
+        draw_children(&children, root.id, "", &mut lines);
+    }
+    lines
+}
+
+fn draw_children<'a>(
+    children: &HashMap<Option<i64>, Vec<&'a Entity>>,
+    parent: i64,
+    prefix: &str,
+    lines: &mut Vec<(String, &'a Entity)>,
+) {
+    let nodes = children.get(&Some(parent)).map_or(&[][..], Vec::as_slice);
+    for (index, node) in nodes.iter().enumerate() {
+        let last = index + 1 == nodes.len();
+        let (branch, indent) = if last {
+            ("└── ", "    ")
+        } else {
+            ("├── ", "│   ")
+        };
+        lines.push((format!("{prefix}{branch}{}", leaf_name(&node.name)), *node));
+        draw_children(children, node.id, &format!("{prefix}{indent}"), lines);
+    }
+}
@@ -1,6 +1,454 @@
 use crate::core::Core;
+use crate::dates;
+use crate::duration::format_minutes;
+use crate::entity::{parent_name, EntityKind};
+use crate::filter::EntryFilter;
+use chrono::{Datelike, Duration, Local, NaiveDate, Weekday};
+use comfy_table::{CellAlignment, Table};
+use rusqlite::params_from_iter;
+use std::collections::BTreeMap;
+
+const SPARK_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+const BAR_WIDTH: usize = 30;
+
+/// Periods longer than this are bucketed by ISO week instead of by day.
+const MAX_DAILY_BUCKETS: i64 = 31;
+
+pub fn handle_stats_command(
+    period: Option<String>,
+    chart: bool,
+    top: usize,
+    rollup: bool,
+    core: &Core,
+) -> anyhow::Result<()> {
+    let dates = match period.as_deref() {
+        Some(period) => dates::parse_span(period, Local::now().date_naive())?,
+        None => match full_range(core)? {
+            Some(range) => range,
+            None => {
+                println!("No entries found for this period.");
+                return Ok(());
+            }
+        },
+    };
+    let filter = EntryFilter {
+        dates: Some(dates),
+        ..Default::default()
+    };
+
+    let daily = entries_per_day(core, &filter)?;
+    let total: i64 = daily.values().sum();
+    if total == 0 {
+        println!("No entries found for this period.");
+        return Ok(());
+    }
+
+    let (from, to) = dates;
+    println!("Statistics for {} to {}", from, to);
+    println!();
+    let minutes = tracked_minutes(core, &filter)?;
+    print_summary(&daily, total, minutes, dates);
+
+    if to - from < Duration::days(MAX_DAILY_BUCKETS) {
+        let buckets = from
+            .iter_days()
+            .take_while(|day| *day <= to)
+            .map(|day| (day.to_string(), daily.get(&day).copied().unwrap_or(0)))
+            .collect::<Vec<_>>();
+        print_activity("Day", &buckets, chart);
+    } else {
+        print_activity("Week", &per_week(&daily, dates), chart);
+    }
+
+    print_weekdays(&busiest_weekdays(core, &filter)?, chart);
+
+    for kind in EntityKind::ALL {
+        let counts = entity_totals(core, &filter, kind, rollup, Measure::Entries)?;
+        print_top(kind.table(), &counts, top);
+        if kind == EntityKind::Project {
+            // Rolled-up parents already include their children's entries.
+            let shares = counts
+                .into_iter()
+                .filter(|(name, _)| !rollup || parent_name(name).is_none())
+                .collect::<Vec<_>>();
+            print_project_share(&shares, chart);
+        }
+    }
+
+    if minutes > 0 {
+        for kind in EntityKind::ALL {
+            let times = entity_totals(core, &filter, kind, rollup, Measure::Minutes)?;
+            print_time(kind.noun(), &times, minutes, chart);
+        }
+    }
 
-pub fn handle_stats_command(_period: Option<String>, _core: &Core) -> anyhow::Result<()> {
-    println!("Statistics functionality coming soon!");
     Ok(())
 }
+
+fn full_range(core: &Core) -> rusqlite::Result<Option<(NaiveDate, NaiveDate)>> {
+    let (min, max): (Option<String>, Option<String>) =
+        core.conn
+            .query_row("SELECT MIN(date), MAX(date) FROM entries", [], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?;
+
+    Ok(min.zip(max).and_then(|(min, max)| {
+        Some((
+            NaiveDate::parse_from_str(&min, "%Y-%m-%d").ok()?,
+            NaiveDate::parse_from_str(&max, "%Y-%m-%d").ok()?,
+        ))
+    }))
+}
+
+/// Builds ` WHERE ...` for the filter, or an empty string.
+fn where_clause(core: &Core, filter: &EntryFilter) -> rusqlite::Result<(String, Vec<String>)> {
+    let (conditions, params) = filter.conditions(&core.conn)?;
+    Ok(if conditions.is_empty() {
+        (String::new(), params)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), params)
+    })
+}
+
+fn entries_per_day(
+    core: &Core,
+    filter: &EntryFilter,
+) -> rusqlite::Result<BTreeMap<NaiveDate, i64>> {
+    let (where_clause, params) = where_clause(core, filter)?;
+    let mut stmt = core.conn.prepare(&format!(
+        "SELECT e.date, COUNT(*) FROM entries e{} GROUP BY e.date ORDER BY e.date",
+        where_clause
+    ))?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut daily = BTreeMap::new();
+    for row in rows {
+        let (date, count) = row?;
+        if let Ok(date) = NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            daily.insert(date, count);
+        }
+    }
+    Ok(daily)
+}
+
+fn busiest_weekdays(core: &Core, filter: &EntryFilter) -> rusqlite::Result<Vec<(String, i64)>> {
+    let (where_clause, params) = where_clause(core, filter)?;
+    let mut stmt = core.conn.prepare(&format!(
+        "SELECT CAST(strftime('%w', e.date) AS INTEGER), COUNT(*) FROM entries e{} GROUP BY 1",
+        where_clause
+    ))?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        Ok((row.get::<_, u32>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut counts = [0; 7];
+    for row in rows {
+        let (weekday, count) = row?;
+        counts[weekday as usize % 7] = count;
+    }
+
+    // SQLite numbers weekdays from Sunday; list them Monday first.
+    Ok((0..7)
+        .map(|i| {
+            let weekday = Weekday::try_from(i as u8).unwrap_or(Weekday::Mon);
+            (
+                format!("{:?}", weekday),
+                counts[(weekday.num_days_from_sunday()) as usize],
+            )
+        })
+        .collect())
+}
+
+/// What [`entity_totals`] adds up per entity.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Measure {
+    /// Number of entries
+    Entries,
+    /// Minutes noted with duration markers
+    Minutes,
+}
+
+impl Measure {
+    fn aggregate(self) -> &'static str {
+        match self {
+            Measure::Entries => "COUNT(*)",
+            Measure::Minutes => "COALESCE(SUM(minutes), 0)",
+        }
+    }
+}
+
+/// Totals the entries (or minutes) of each person, project or tag, largest
+/// first; entities with none are left out. With `rollup`, a project or tag
+/// also counts everything nested below it, each entry once.
+pub fn entity_totals(
+    core: &Core,
+    filter: &EntryFilter,
+    kind: EntityKind,
+    rollup: bool,
+    measure: Measure,
+) -> rusqlite::Result<Vec<(String, i64)>> {
+    let (entity_table, link_table, id_column) = (kind.table(), kind.link_table(), kind.id_column());
+    let (where_clause, params) = where_clause(core, filter)?;
+    let aggregate = measure.aggregate();
+    let query = if rollup && kind.is_hierarchical() {
+        format!(
+            "WITH RECURSIVE tree(ancestor, id) AS (
+                 SELECT id, id FROM {entity_table}
+                 UNION ALL SELECT tree.ancestor, x.id FROM {entity_table} x
+                 JOIN tree ON x.parent_id = tree.id
+             )
+             SELECT a.name, {aggregate} FROM (
+                 SELECT DISTINCT tree.ancestor, e.id, e.minutes FROM tree
+                 JOIN {link_table} l ON l.{id_column} = tree.id
+                 JOIN entries e ON e.id = l.entry_id{where_clause}
+             ) JOIN {entity_table} a ON a.id = ancestor
+             GROUP BY a.id HAVING {aggregate} > 0 ORDER BY 2 DESC, a.name"
+        )
+    } else {
+        format!(
+            "SELECT x.name, {aggregate} FROM (
+                 SELECT l.{id_column} AS entity_id, e.minutes FROM {link_table} l
+                 JOIN entries e ON e.id = l.entry_id{where_clause}
+             ) JOIN {entity_table} x ON x.id = entity_id
+             GROUP BY x.id HAVING {aggregate} > 0 ORDER BY 2 DESC, x.name"
+        )
+    };
+    let mut stmt = core.conn.prepare(&query)?;
+    let rows = stmt.query_map(params_from_iter(params.iter()), |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    })?;
+    rows.collect()
+}
+
+/// Minutes noted in the entries matching `filter`.
+pub fn tracked_minutes(core: &Core, filter: &EntryFilter) -> rusqlite::Result<i64> {
+    let (where_clause, params) = where_clause(core, filter)?;
+    core.conn.query_row(
+        &format!("SELECT COALESCE(SUM(e.minutes), 0) FROM entries e{where_clause}"),
+        params_from_iter(params.iter()),
+        |row| row.get(0),
+    )
+}
+
+fn per_week(
+    daily: &BTreeMap<NaiveDate, i64>,
+    (from, to): (NaiveDate, NaiveDate),
+) -> Vec<(String, i64)> {
+    let mut weeks = BTreeMap::new();
+    let mut day = from;
+    while day <= to {
+        let week = day.iso_week();
+        *weeks.entry((week.year(), week.week())).or_insert(0) +=
+            daily.get(&day).copied().unwrap_or(0);
+        day += Duration::days(1);
+    }
+
+    weeks
+        .into_iter()
+        .map(|((year, week), count)| (format!("{}-W{:02}", year, week), count))
+        .collect()
+}
+
+/// Longest run of consecutive active days, and the run ending on the last day
+/// of the period.
+fn streaks(daily: &BTreeMap<NaiveDate, i64>, last_day: NaiveDate) -> (i64, i64) {
+    let mut longest = 0;
+    let mut run = 0;
+    let mut previous: Option<NaiveDate> = None;
+
+    for (&day, &count) in daily {
+        if count == 0 {
+            continue;
+        }
+        run = match previous {
+            Some(prev) if day - prev == Duration::days(1) => run + 1,
+            _ => 1,
+        };
+        longest = longest.max(run);
+        previous = Some(day);
+    }
+
+    let current = if previous == Some(last_day) { run } else { 0 };
+    (longest, current)
+}
+
+fn print_summary(
+    daily: &BTreeMap<NaiveDate, i64>,
+    total: i64,
+    minutes: i64,
+    (from, to): (NaiveDate, NaiveDate),
+) {
+    let days = (to - from).num_days() + 1;
+    let active = daily.values().filter(|count| **count > 0).count();
+    let (longest, current) = streaks(daily, to);
+
+    let mut table = Table::new();
+    table.set_header(vec!["Summary", ""]);
+    table.add_row(vec!["Entries".to_string(), total.to_string()]);
+    table.add_row(vec![
+        "Active days".to_string(),
+        format!("{} of {}", active, days),
+    ]);
+    table.add_row(vec![
+        "Entries per active day".to_string(),
+        format!("{:.1}", total as f64 / active.max(1) as f64),
+    ]);
+    table.add_row(vec![
+        "Longest streak".to_string(),
+        format!("{} days", longest),
+    ]);
+    table.add_row(vec![
+        "Current streak".to_string(),
+        format!("{} days", current),
+    ]);
+    if minutes > 0 {
+        table.add_row(vec!["Tracked time".to_string(), format_minutes(minutes)]);
+    }
+    println!("{table}");
+}
+
+fn print_activity(label: &str, buckets: &[(String, i64)], chart: bool) {
+    let counts = buckets.iter().map(|(_, count)| *count).collect::<Vec<_>>();
+    let max = counts.iter().copied().max().unwrap_or(0);
+
+    let mut table = Table::new();
+    let mut header = vec![label, "Entries"];
+    if chart {
+        header.push("");
+    }
+    table.set_header(header);
+    for (bucket, count) in buckets {
+        let mut row = vec![bucket.clone(), count.to_string()];
+        if chart {
+            row.push(bar(*count, max));
+        }
+        table.add_row(row);
+    }
+    right_align(&mut table, 1);
+    println!("{table}");
+
+    if chart {
+        println!("{}", sparkline(&counts));
+    }
+}
+
+fn print_weekdays(weekdays: &[(String, i64)], chart: bool) {
+    let max = weekdays.iter().map(|(_, count)| *count).max().unwrap_or(0);
+
+    let mut table = Table::new();
+    let mut header = vec!["Weekday", "Entries"];
+    if chart {
+        header.push("");
+    }
+    table.set_header(header);
+    for (weekday, count) in weekdays {
+        let mut row = vec![weekday.clone(), count.to_string()];
+        if chart {
+            row.push(bar(*count, max));
+        }
+        table.add_row(row);
+    }
+    right_align(&mut table, 1);
+    println!("{table}");
+}
+
+fn print_top(title: &str, counts: &[(String, i64)], top: usize) {
+    if counts.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+    table.set_header(vec![format!("Top {}", title), "Entries".to_string()]);
+    for (name, count) in counts.iter().take(top) {
+        table.add_row(vec![name.clone(), count.to_string()]);
+    }
+    right_align(&mut table, 1);
+    println!("{table}");
+}
+
+fn print_project_share(counts: &[(String, i64)], chart: bool) {
+    let total: i64 = counts.iter().map(|(_, count)| count).sum();
+    if total == 0 {
+        return;
+    }
+
+    let mut table = Table::new();
+    let mut header = vec!["Project", "Share"];
+    if chart {
+        header.push("");
+    }
+    table.set_header(header);
+    for (name, count) in counts {
+        let mut row = vec![
+            name.clone(),
+            format!("{:.1}%", *count as f64 * 100.0 / total as f64),
+        ];
+        if chart {
+            row.push(bar(*count, total));
+        }
+        table.add_row(row);
+    }
+    right_align(&mut table, 1);
+    println!("{table}");
+}
+
+/// Time per person, project or tag, with its share of the `total` minutes
+/// tracked in the period. An entry mentioning two projects counts for both.
+pub fn print_time(noun: &str, times: &[(String, i64)], total: i64, chart: bool) {
+    if times.is_empty() {
+        return;
+    }
+
+    let mut table = Table::new();
+    let heading = format!("Time per {}", noun);
+    let mut header = vec![heading.as_str(), "Time", "Share"];
+    if chart {
+        header.push("");
+    }
+    table.set_header(header);
+    for (name, minutes) in times {
+        let mut row = vec![
+            name.clone(),
+            format_minutes(*minutes),
+            format!("{:.1}%", *minutes as f64 * 100.0 / total.max(1) as f64),
+        ];
+        if chart {
+            row.push(bar(*minutes, total));
+        }
+        table.add_row(row);
+    }
+    right_align(&mut table, 1);
+    right_align(&mut table, 2);
+    println!("{table}");
+}
+
+pub fn right_align(table: &mut Table, column: usize) {
+    if let Some(column) = table.column_mut(column) {
+        column.set_cell_alignment(CellAlignment::Right);
+    }
+}
+
+pub fn bar(value: i64, max: i64) -> String {
+    if max <= 0 {
+        return String::new();
+    }
+    let width = (value as f64 / max as f64 * BAR_WIDTH as f64).round() as usize;
+    "█".repeat(width.max(usize::from(value > 0)))
+}
+
+pub fn sparkline(values: &[i64]) -> String {
+    let max = values.iter().copied().max().unwrap_or(0);
+    values
+        .iter()
+        .map(|&value| {
+            if max <= 0 || value <= 0 {
+                ' '
+            } else {
+                let level = (value as f64 / max as f64 * (SPARK_LEVELS.len() - 1) as f64).round();
+                SPARK_LEVELS[level as usize]
+            }
+        })
+        .collect()
+}
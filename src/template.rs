@@ -0,0 +1,536 @@
+//! A small template language for reports.
+//!
+//! - `{{ entry.content }}` prints a value; lists print comma-separated.
+//! - `{{ minutes | hours | times:85 | round:2 }}` passes a value through
+//!   filters (see [`FILTERS`]); they work in `for` and `if` tags too.
+//! - `{% for project in projects %} ... {% endfor %}` repeats its body, with
+//!   `loop.index`, `loop.first` and `loop.last` available inside.
+//! - `{% if project.people %} ... {% else %} ... {% endif %}` tests a value
+//!   (`{% if not ... %}` its opposite); `false`, `null`, `0`, empty strings and
+//!   empty lists are false.
+//!
+//! A line holding nothing but a `{% ... %}` tag leaves no trace in the output.
+
+use crate::duration::format_minutes;
+use anyhow::{anyhow, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::NaiveDate;
+use serde_json::{json, Number, Value};
+use std::cmp::Ordering;
+use std::fmt::Write;
+
+/// Filters `{{ value | filter }}` understands, with what they do.
+pub const FILTERS: &[(&str, &str)] = &[
+    ("upper", "upper case"),
+    ("lower", "lower case"),
+    ("capitalize", "first letter upper case"),
+    ("length", "number of items or characters"),
+    ("join:SEP", "list items joined by SEP (default \", \")"),
+    ("first", "first item of a list"),
+    ("last", "last item of a list"),
+    ("sort:FIELD", "list sorted, by FIELD of each item if given"),
+    ("reverse", "list in reverse order"),
+    (
+        "sum:FIELD",
+        "total of a list of numbers, or of FIELD of each item",
+    ),
+    ("default:TEXT", "TEXT if the value is empty"),
+    ("time", "minutes as 1h 30m"),
+    ("hours", "minutes as hours"),
+    ("times:N", "number multiplied by N"),
+    ("round:N", "number with N decimals (default 0)"),
+    (
+        "date:FORMAT",
+        "YYYY-MM-DD date in a strftime FORMAT such as %d.%m.%Y",
+    ),
+];
+
+/// Most decimals `round` prints.
+const MAX_DECIMALS: usize = 15;
+
+/// A dotted path into the context, with filters applied in turn.
+#[derive(Debug)]
+struct Expr {
+    source: String,
+    path: String,
+    filters: Vec<(String, Option<String>)>,
+}
+
+#[derive(Debug)]
+enum Node {
+    Text(String),
+    Value(Expr),
+    For {
+        name: String,
+        list: Expr,
+        body: Vec<Node>,
+    },
+    If {
+        condition: Expr,
+        negated: bool,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// A parsed template, ready to render against a JSON context.
+#[derive(Debug)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+/// A `{{ }}` or `{% %}` tag, or the text between tags.
+enum Token<'a> {
+    Text(&'a str),
+    Value(&'a str, usize),
+    Tag(&'a str, usize),
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut tokens = tokenize(source)?.into_iter();
+        let (nodes, end) = parse_nodes(&mut tokens)?;
+        match end {
+            None => Ok(Self { nodes }),
+            Some((tag, line)) => Err(anyhow!("line {}: unexpected {{% {} %}}", line, tag)),
+        }
+    }
+
+    pub fn render(&self, context: &Value) -> Result<String> {
+        let mut out = String::new();
+        let mut scopes = Vec::new();
+        render_nodes(&self.nodes, context, &mut scopes, &mut out)?;
+        Ok(out)
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+    // Whether `rest` starts at the beginning of a line of the source.
+    let mut line_start = true;
+
+    while let Some(start) = rest.find('{') {
+        let open = &rest[start..];
+        let close = if open.starts_with("{{") {
+            "}}"
+        } else if open.starts_with("{%") {
+            "%}"
+        } else {
+            // A lone brace is text.
+            tokens.push(Token::Text(&rest[..start + 1]));
+            rest = &rest[start + 1..];
+            line_start = false;
+            continue;
+        };
+        let line = source[..source.len() - open.len()].matches('\n').count() + 1;
+        let end = open
+            .find(close)
+            .ok_or_else(|| anyhow!("line {}: unclosed {}", line, &open[..2]))?;
+        let inner = open[2..end].trim();
+
+        let mut text = &rest[..start];
+        let mut after = &open[end + 2..];
+        let mut standalone = false;
+        if close == "%}" {
+            // Drop the line of a tag that stands alone on it.
+            let indent_start = text.rfind('\n').map(|i| i + 1);
+            let indent = &text[indent_start.unwrap_or(0)..];
+            let line_end = after.find('\n');
+            let trailing = &after[..line_end.unwrap_or(after.len())];
+            if (indent_start.is_some() || line_start)
+                && indent.trim().is_empty()
+                && trailing.trim().is_empty()
+            {
+                text = &text[..indent_start.unwrap_or(0)];
+                after = line_end.map_or("", |i| &after[i + 1..]);
+                standalone = true;
+            }
+        }
+
+        if !text.is_empty() {
+            tokens.push(Token::Text(text));
+        }
+        tokens.push(if close == "}}" {
+            Token::Value(inner, line)
+        } else {
+            Token::Tag(inner, line)
+        });
+        rest = after;
+        line_start = standalone;
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+    Ok(tokens)
+}
+
+/// The `else`, `endfor` or `endif` tag that ends a block, and its line.
+type BlockEnd<'a> = Option<(&'a str, usize)>;
+
+/// Parses nodes up to the next `else`, `endfor` or `endif`.
+fn parse_nodes<'a>(
+    tokens: &mut impl Iterator<Item = Token<'a>>,
+) -> Result<(Vec<Node>, BlockEnd<'a>)> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text(text) => nodes.push(Node::Text(text.to_string())),
+            Token::Value(expr, line) => nodes.push(Node::Value(parse_expr(expr, line)?)),
+            Token::Tag(tag, line) => {
+                let (keyword, rest) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+                let rest = rest.trim();
+                match keyword {
+                    "for" => {
+                        let (name, list) = rest
+                            .split_once(" in ")
+                            .map(|(name, list)| (name.trim(), list))
+                            .filter(|(name, _)| is_identifier(name))
+                            .ok_or_else(|| {
+                                anyhow!("line {}: expected {{% for NAME in LIST %}}", line)
+                            })?;
+                        let list = parse_expr(list, line)?;
+                        let (body, end) = parse_nodes(tokens)?;
+                        expect_end(end, "endfor", line)?;
+                        nodes.push(Node::For {
+                            name: name.to_string(),
+                            list,
+                            body,
+                        });
+                    }
+                    "if" => {
+                        let (negated, condition) = match rest.strip_prefix("not ") {
+                            Some(condition) => (true, condition),
+                            None => (false, rest),
+                        };
+                        let condition = parse_expr(condition, line)?;
+                        let (then, end) = parse_nodes(tokens)?;
+                        let otherwise = if matches!(end, Some(("else", _))) {
+                            let (otherwise, end) = parse_nodes(tokens)?;
+                            expect_end(end, "endif", line)?;
+                            otherwise
+                        } else {
+                            expect_end(end, "endif", line)?;
+                            Vec::new()
+                        };
+                        nodes.push(Node::If {
+                            condition,
+                            negated,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    "else" | "endfor" | "endif" if rest.is_empty() => {
+                        return Ok((nodes, Some((tag, line))))
+                    }
+                    _ => return Err(anyhow!("line {}: unknown tag {{% {} %}}", line, tag)),
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '.')
+}
+
+/// Parses `path | filter | filter:arg`, where an argument may be quoted.
+fn parse_expr(source: &str, line: usize) -> Result<Expr> {
+    let mut parts = Vec::new();
+    let (mut start, mut quoted) = (0, false);
+    for (index, c) in source.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            '|' if !quoted => {
+                parts.push(&source[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    if quoted {
+        return Err(anyhow!("line {}: unclosed quote in '{}'", line, source));
+    }
+    parts.push(&source[start..]);
+
+    let path = parts[0].trim();
+    if !is_identifier(path) {
+        return Err(anyhow!("line {}: '{}' is not a value name", line, path));
+    }
+    let filters = parts[1..]
+        .iter()
+        .map(|filter| {
+            let (name, arg) = match filter.split_once(':') {
+                Some((name, arg)) => {
+                    let arg = arg.trim();
+                    let arg = arg
+                        .strip_prefix('"')
+                        .and_then(|arg| arg.strip_suffix('"'))
+                        .unwrap_or(arg);
+                    (name.trim(), Some(arg.to_string()))
+                }
+                None => (filter.trim(), None),
+            };
+            let known = FILTERS
+                .iter()
+                .any(|(filter, _)| filter.split(':').next() == Some(name));
+            if !known {
+                return Err(anyhow!("line {}: unknown filter '{}'", line, name));
+            }
+            Ok((name.to_string(), arg))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(Expr {
+        source: source.trim().to_string(),
+        path: path.to_string(),
+        filters,
+    })
+}
+
+fn expect_end(end: BlockEnd, expected: &str, line: usize) -> Result<()> {
+    match end {
+        Some((tag, _)) if tag == expected => Ok(()),
+        Some((tag, end_line)) => Err(anyhow!(
+            "line {}: expected {{% {} %}} for the block opened on line {}, found {{% {} %}}",
+            end_line,
+            expected,
+            line,
+            tag
+        )),
+        None => Err(anyhow!(
+            "line {}: block is never closed with {{% {} %}}",
+            line,
+            expected
+        )),
+    }
+}
+
+fn render_nodes(
+    nodes: &[Node],
+    context: &Value,
+    scopes: &mut Vec<(String, Value)>,
+    out: &mut String,
+) -> Result<()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Value(expr) => out.push_str(&display(&evaluate(expr, context, scopes)?)),
+            Node::For { name, list, body } => {
+                let items = match evaluate(list, context, scopes)? {
+                    Value::Array(items) => items,
+                    Value::Null => Vec::new(),
+                    other => return Err(anyhow!("'{}' is not a list: {}", list.source, other)),
+                };
+                let count = items.len();
+                for (index, item) in items.into_iter().enumerate() {
+                    let state = json!({
+                        "index": index + 1,
+                        "first": index == 0,
+                        "last": index + 1 == count,
+                    });
+                    scopes.push(("loop".to_string(), state));
+                    scopes.push((name.clone(), item));
+                    let result = render_nodes(body, context, scopes, out);
+                    scopes.truncate(scopes.len() - 2);
+                    result?;
+                }
+            }
+            Node::If {
+                condition,
+                negated,
+                then,
+                otherwise,
+            } => {
+                let branch = if is_truthy(&evaluate(condition, context, scopes)?) != *negated {
+                    then
+                } else {
+                    otherwise
+                };
+                render_nodes(branch, context, scopes, out)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Resolves a dotted path, looking in loop variables (innermost first) before
+/// the context. Missing values are `null`.
+fn lookup(path: &str, context: &Value, scopes: &[(String, Value)]) -> Value {
+    let mut parts = path.split('.');
+    let first = parts.next().unwrap_or_default();
+    let mut value = scopes
+        .iter()
+        .rev()
+        .find(|(name, _)| name == first)
+        .map(|(_, value)| value)
+        .or_else(|| context.get(first));
+    for part in parts {
+        value = value.and_then(|value| value.get(part));
+    }
+    value.cloned().unwrap_or(Value::Null)
+}
+
+fn evaluate(expr: &Expr, context: &Value, scopes: &[(String, Value)]) -> Result<Value> {
+    let mut value = lookup(&expr.path, context, scopes);
+    for (name, arg) in &expr.filters {
+        value = apply(name, arg.as_deref(), value)
+            .map_err(|e| anyhow!("in {{{{ {} }}}}: {}", expr.source, e))?;
+    }
+    Ok(value)
+}
+
+fn apply(filter: &str, arg: Option<&str>, value: Value) -> Result<Value> {
+    let required = || arg.ok_or_else(|| anyhow!("'{}' needs an argument", filter));
+    let text = || display(&value);
+    let list = || match &value {
+        Value::Array(items) => Ok(items.clone()),
+        Value::Null => Ok(Vec::new()),
+        other => Err(anyhow!("'{}' needs a list, got {}", filter, other)),
+    };
+    Ok(match filter {
+        "upper" => json!(text().to_uppercase()),
+        "lower" => json!(text().to_lowercase()),
+        "capitalize" => {
+            let text = text();
+            let mut chars = text.chars();
+            json!(chars
+                .next()
+                .map(|first| first.to_uppercase().chain(chars).collect::<String>())
+                .unwrap_or_default())
+        }
+        "length" => json!(match &value {
+            Value::Array(items) => items.len(),
+            Value::Object(fields) => fields.len(),
+            Value::Null => 0,
+            other => display(other).chars().count(),
+        }),
+        "join" => json!(list()?
+            .iter()
+            .map(display)
+            .collect::<Vec<_>>()
+            .join(arg.unwrap_or(", "))),
+        "first" => list()?.into_iter().next().unwrap_or(Value::Null),
+        "last" => list()?.into_iter().last().unwrap_or(Value::Null),
+        "sort" => {
+            let key = |item: &Value| match arg {
+                Some(field) => item.get(field).cloned().unwrap_or(Value::Null),
+                None => item.clone(),
+            };
+            let mut items = list()?;
+            items.sort_by(|a, b| compare(&key(a), &key(b)));
+            Value::Array(items)
+        }
+        "reverse" => Value::Array(list()?.into_iter().rev().collect()),
+        "sum" => {
+            let mut total = 0.0;
+            for item in list()? {
+                let item = match arg {
+                    Some(field) => item.get(field).cloned().unwrap_or(Value::Null),
+                    None => item,
+                };
+                total += number(&item).unwrap_or(0.0);
+            }
+            to_number(total)
+        }
+        "default" => {
+            if is_truthy(&value) {
+                value
+            } else {
+                json!(required()?)
+            }
+        }
+        "time" => json!(match number(&value) {
+            Some(minutes) if minutes > 0.0 => format_minutes(minutes.round() as i64),
+            _ => String::new(),
+        }),
+        "hours" => to_number(number(&value).unwrap_or(0.0) / 60.0),
+        "times" => {
+            let factor = required()?
+                .parse::<f64>()
+                .map_err(|_| anyhow!("'times' needs a number"))?;
+            to_number(number(&value).unwrap_or(0.0) * factor)
+        }
+        "round" => {
+            let digits = match arg {
+                Some(arg) => arg
+                    .parse::<usize>()
+                    .ok()
+                    .filter(|digits| *digits <= MAX_DECIMALS)
+                    .ok_or_else(|| {
+                        anyhow!("'round' needs a number of decimals up to {}", MAX_DECIMALS)
+                    })?,
+                None => 0,
+            };
+            json!(format!("{:.*}", digits, number(&value).unwrap_or(0.0)))
+        }
+        "date" => {
+            let format = required()?;
+            if StrftimeItems::new(format).any(|item| item == Item::Error) {
+                return Err(anyhow!("'{}' is not a valid date format", format));
+            }
+            match NaiveDate::parse_from_str(&text(), "%Y-%m-%d") {
+                Ok(date) => {
+                    // Time and zone specifiers parse but cannot be rendered
+                    // for a date.
+                    let mut formatted = String::new();
+                    write!(formatted, "{}", date.format(format))
+                        .map_err(|_| anyhow!("'{}' is not a valid date format", format))?;
+                    json!(formatted)
+                }
+                Err(_) => value,
+            }
+        }
+        _ => return Err(anyhow!("unknown filter '{}'", filter)),
+    })
+}
+
+/// A number, or a string holding one.
+fn number(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(number) => number.as_f64(),
+        Value::String(text) => text.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// Whole numbers stay integers, so they print without a trailing `.0`.
+fn to_number(value: f64) -> Value {
+    if value.fract() == 0.0 && value.abs() < i64::MAX as f64 {
+        json!(value as i64)
+    } else {
+        Number::from_f64(value).map_or(Value::Null, Value::Number)
+    }
+}
+
+/// Orders numbers numerically and everything else by its text.
+fn compare(a: &Value, b: &Value) -> Ordering {
+    match (number(a), number(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        _ => display(a).to_lowercase().cmp(&display(b).to_lowercase()),
+    }
+}
+
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items.iter().map(display).collect::<Vec<_>>().join(", "),
+        other => other.to_string(),
+    }
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(value) => *value,
+        Value::Number(number) => number.as_f64() != Some(0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
@@ -0,0 +1,154 @@
+use crate::core::Core;
+use crate::duration;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use rusqlite::{params, Connection, OptionalExtension};
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// The running (or paused) timer started with `dia start`.
+#[derive(Debug, Clone)]
+pub struct Timer {
+    pub description: String,
+    pub started_at: DateTime<Utc>,
+    /// Set while the timer is paused.
+    pub paused_at: Option<DateTime<Utc>>,
+    /// Time spent paused before the current pause, if any.
+    pub paused_seconds: i64,
+}
+
+impl Timer {
+    /// Time worked so far, excluding pauses.
+    pub fn elapsed_seconds(&self, now: DateTime<Utc>) -> i64 {
+        let end = self.paused_at.unwrap_or(now);
+        ((end - self.started_at).num_seconds() - self.paused_seconds).max(0)
+    }
+
+    /// Worked minutes rounded to the nearest minute, and at least one.
+    pub fn elapsed_minutes(&self, now: DateTime<Utc>) -> i64 {
+        ((self.elapsed_seconds(now) + 30) / 60).max(1)
+    }
+
+    /// One-line state, e.g. `Running: %Dia refactor parser (since 09:12, 1h 5m)`.
+    pub fn summary(&self, now: DateTime<Utc>) -> String {
+        let state = if self.paused_at.is_some() {
+            "Paused"
+        } else {
+            "Running"
+        };
+        format!(
+            "{}: {} (since {}, {})",
+            state,
+            self.description,
+            self.started_at.with_timezone(&Local).format("%H:%M"),
+            duration::format_minutes(self.elapsed_seconds(now) / 60)
+        )
+    }
+}
+
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+fn parse_timestamp(text: &str) -> rusqlite::Result<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(text, TIMESTAMP_FORMAT)
+        .map(|time| time.and_utc())
+        .map_err(|e| {
+            rusqlite::Error::FromSqlConversionFailure(0, rusqlite::types::Type::Text, e.into())
+        })
+}
+
+/// The current timer, if one is running or paused.
+pub fn current(conn: &Connection) -> rusqlite::Result<Option<Timer>> {
+    conn.query_row(
+        "SELECT description, started_at, paused_at, paused_seconds FROM timer WHERE id = 1",
+        [],
+        |row| {
+            Ok(Timer {
+                description: row.get(0)?,
+                started_at: parse_timestamp(&row.get::<_, String>(1)?)?,
+                paused_at: row
+                    .get::<_, Option<String>>(2)?
+                    .map(|text| parse_timestamp(&text))
+                    .transpose()?,
+                paused_seconds: row.get(3)?,
+            })
+        },
+    )
+    .optional()
+}
+
+fn require(conn: &Connection) -> Result<Timer> {
+    current(conn)?.ok_or_else(|| anyhow!("No timer is running; start one with `dia start`"))
+}
+
+/// Starts a timer for `description`, refusing if another one exists.
+pub fn start(core: &Core, description: &str) -> Result<Timer> {
+    let description = description.trim();
+    if description.is_empty() {
+        return Err(anyhow!("Describe what you are working on"));
+    }
+    if let Some(timer) = current(&core.conn)? {
+        return Err(anyhow!(
+            "A timer is already running for '{}'; stop it with `dia stop` first",
+            timer.description
+        ));
+    }
+
+    core.conn.execute(
+        "INSERT INTO timer (id, description, started_at) VALUES (1, ?, ?)",
+        params![description, timestamp(Utc::now())],
+    )?;
+    require(&core.conn)
+}
+
+/// Pauses the running timer.
+pub fn pause(core: &Core) -> Result<Timer> {
+    let timer = require(&core.conn)?;
+    if timer.paused_at.is_some() {
+        return Err(anyhow!("The timer is already paused"));
+    }
+    core.conn.execute(
+        "UPDATE timer SET paused_at = ? WHERE id = 1",
+        params![timestamp(Utc::now())],
+    )?;
+    require(&core.conn)
+}
+
+/// Resumes a paused timer, adding the pause to the time not worked.
+pub fn resume(core: &Core) -> Result<Timer> {
+    let timer = require(&core.conn)?;
+    let Some(paused_at) = timer.paused_at else {
+        return Err(anyhow!("The timer is not paused"));
+    };
+    let paused = (Utc::now() - paused_at).num_seconds().max(0);
+    core.conn.execute(
+        "UPDATE timer SET paused_at = NULL, paused_seconds = paused_seconds + ? WHERE id = 1",
+        params![paused],
+    )?;
+    require(&core.conn)
+}
+
+/// Stops the timer and logs its description with the time worked as an
+/// entry on the day it was started, returning the entry's text. With
+/// `discard`, the timer is dropped without logging anything.
+pub fn stop(core: &mut Core, discard: bool) -> Result<Option<String>> {
+    let timer = require(&core.conn)?;
+    let tx = core.conn.transaction()?;
+    tx.execute("DELETE FROM timer WHERE id = 1", [])?;
+
+    let content = (!discard).then(|| {
+        format!(
+            "{} {}",
+            timer.description,
+            duration::marker(timer.elapsed_minutes(Utc::now()))
+        )
+    });
+    if let Some(content) = &content {
+        let date = timer.started_at.with_timezone(&Local).date_naive();
+        Core::insert_entry(&tx, &core.policy, content, date, None)?;
+    }
+
+    tx.commit()?;
+    Ok(content)
+}
@@ -0,0 +1,78 @@
+mod common;
+
+use common::TestDiary;
+
+#[test]
+fn aliases_resolve_when_logging() {
+    let diary = TestDiary::new();
+    diary.log("Kickoff with @JohnK", "2025-03-01");
+    diary.ok(&["alias", "add", "@jk", "@JohnK"]);
+    diary.ok(&["alias", "add", "@john", "JohnK"]);
+
+    diary.log("Pairing with @jk and @JOHN", "2025-03-02");
+
+    let people = diary.ok(&["show", "people"]);
+    assert!(people.contains("JohnK"));
+    assert!(!people.contains(" jk "));
+    let out = diary.ok(&["show", "entries", "--person", "JohnK"]);
+    assert!(out.contains("Kickoff"));
+    assert!(out.contains("Pairing with @jk and @JOHN"));
+
+    // Filters resolve aliases too.
+    let out = diary.ok(&["show", "entries", "--person", "jk"]);
+    assert!(out.contains("Kickoff"));
+    assert!(out.contains("Pairing with @jk and @JOHN"));
+}
+
+#[test]
+fn list_and_remove() {
+    let diary = TestDiary::new();
+    diary.ok(&["alias", "add", "%web", "%Website"]);
+    diary.ok(&["alias", "add", "#fix", "bugfix"]);
+
+    let out = diary.ok(&["alias", "list"]);
+    assert!(out.contains("%web"));
+    assert!(out.contains("%Website"));
+    assert!(out.contains("#fix"));
+
+    assert!(diary
+        .ok(&["alias", "remove", "%web"])
+        .contains("Removed alias %web."));
+    assert!(!diary.ok(&["alias", "list"]).contains("%web"));
+    assert!(!diary.run(&["alias", "remove", "%web"]).status.success());
+
+    diary.ok(&["alias", "remove", "#fix"]);
+    assert!(diary.ok(&["alias", "list"]).contains("No aliases defined."));
+}
+
+#[test]
+fn rejects_aliases_that_would_hide_data() {
+    let diary = TestDiary::new();
+    diary.log("Met @jk", "2025-03-01");
+    diary.ok(&["alias", "add", "@john", "@JohnK"]);
+
+    // @jk already has entries of its own; it must be merged instead.
+    let output = diary.run(&["alias", "add", "@jk", "@JohnK"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("entity merge"));
+
+    // Chains are refused rather than resolved transitively.
+    assert!(!diary.run(&["alias", "add", "@j", "@john"]).status.success());
+    assert!(!diary
+        .run(&["alias", "add", "@JohnK", "@johnk"])
+        .status
+        .success());
+}
+
+#[test]
+fn renaming_the_canonical_entity_keeps_aliases_working() {
+    let diary = TestDiary::new();
+    diary.log("Kickoff with @JohnK", "2025-03-01");
+    diary.ok(&["alias", "add", "@jk", "@JohnK"]);
+    diary.ok(&["entity", "rename", "@JohnK", "@John"]);
+
+    assert!(diary.ok(&["alias", "list"]).contains("@John "));
+    diary.log("Lunch with @jk", "2025-03-02");
+    let out = diary.ok(&["show", "entries", "--person", "John"]);
+    assert!(out.contains("Lunch with @jk"));
+}
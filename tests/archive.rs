@@ -0,0 +1,110 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+use serde_json::Value;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Paired with @Alice on %Dia #parser", "2025-03-01");
+    diary.log("Deployed %Website", "2025-03-02");
+    diary
+}
+
+fn export(diary: &TestDiary) -> std::path::PathBuf {
+    let path = diary.path().join("backup.json");
+    diary.ok(&["export", "--format", "json", "-o", path.to_str().unwrap()]);
+    path
+}
+
+#[test]
+fn json_export_is_a_versioned_archive() {
+    let diary = seeded();
+    let archive: Value = serde_json::from_str(&diary.ok(&["export", "-f", "json"])).unwrap();
+
+    assert_eq!(archive["version"], 1);
+    let entries = archive["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["people"], serde_json::json!(["Alice"]));
+    assert_eq!(entries[0]["review"], Value::Null);
+}
+
+#[test]
+fn round_trip_preserves_links_timestamps_and_review_state() {
+    let source = seeded();
+    let conn = Connection::open(source.db_path()).unwrap();
+    conn.execute_batch(
+        "UPDATE entries SET created_at = '2025-03-01 08:30:00', last_reviewed = '2025-03-05' WHERE id = 1;
+         INSERT INTO review_state VALUES (1, 2.6, 6, 2, '2025-03-11');
+         -- Links that no longer match the text must survive as they are.
+         INSERT INTO projects (name) VALUES ('Legacy');
+         INSERT INTO entry_projects VALUES (2, (SELECT id FROM projects WHERE name = 'Legacy'));",
+    )
+    .unwrap();
+    let path = export(&source);
+
+    let target = TestDiary::new();
+    let out = target.ok(&["import", path.to_str().unwrap()]);
+    assert!(out.contains("Imported 2 entries (0 duplicates skipped)"));
+
+    let conn = Connection::open(target.db_path()).unwrap();
+    let (created_at, last_reviewed): (String, String) = conn
+        .query_row(
+            "SELECT created_at, last_reviewed FROM entries WHERE content LIKE 'Paired%'",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .unwrap();
+    assert_eq!(created_at, "2025-03-01 08:30:00");
+    assert_eq!(last_reviewed, "2025-03-05");
+
+    let review: (f64, i64, i64, String) = conn
+        .query_row(
+            "SELECT ease, interval_days, repetitions, due_date FROM review_state",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+        )
+        .unwrap();
+    assert_eq!(review, (2.6, 6, 2, "2025-03-11".to_string()));
+
+    let out = target.ok(&["show", "entries", "--project", "Legacy"]);
+    assert!(out.contains("Deployed %Website"));
+    let out = target.ok(&["show", "entries", "--tag", "parser"]);
+    assert!(out.contains("Paired with"));
+}
+
+#[test]
+fn import_skips_duplicates() {
+    let diary = seeded();
+    let path = export(&diary);
+
+    let out = diary.ok(&["import", path.to_str().unwrap()]);
+    assert!(out.contains("Imported 0 entries (2 duplicates skipped)"));
+
+    let count: i64 = Connection::open(diary.db_path())
+        .unwrap()
+        .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn invalid_archive_is_rejected_without_changes() {
+    let diary = seeded();
+    let path = diary.path().join("broken.json");
+    std::fs::write(
+        &path,
+        r#"{"version": 1, "entries": [
+            {"date": "2025-04-01", "created_at": "2025-04-01T09:00:00Z", "content": "Fine"},
+            {"date": "not a date", "created_at": "2025-04-01T09:00:00Z", "content": "Broken"}
+        ]}"#,
+    )
+    .unwrap();
+
+    let output = diary.run(&["import", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Entry 2 in archive"));
+
+    let out = diary.ok(&["show", "entries"]);
+    assert!(!out.contains("Fine"));
+}
@@ -0,0 +1,67 @@
+use rusqlite::Connection;
+use std::path::Path;
+use std::process::{Command, Output};
+use tempfile::TempDir;
+
+/// A throwaway diary: every `dia` invocation runs with its config (and
+/// therefore `diary.db`) inside a fresh temporary directory.
+pub struct TestDiary {
+    dir: TempDir,
+}
+
+#[allow(dead_code)]
+impl TestDiary {
+    pub fn new() -> Self {
+        Self {
+            dir: TempDir::new().expect("failed to create temp dir"),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        self.dir.path()
+    }
+
+    pub fn db_path(&self) -> std::path::PathBuf {
+        self.dir.path().join("dia").join("diary.db")
+    }
+
+    pub fn command(&self) -> Command {
+        let mut cmd = Command::new(env!("CARGO_BIN_EXE_dia"));
+        cmd.env("XDG_CONFIG_HOME", self.dir.path())
+            .env("HOME", self.dir.path());
+        cmd
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.command()
+            .args(args)
+            .output()
+            .expect("failed to run dia")
+    }
+
+    /// Runs `dia` and returns stdout, panicking with stderr on failure.
+    pub fn ok(&self, args: &[&str]) -> String {
+        let output = self.run(args);
+        assert!(
+            output.status.success(),
+            "dia {:?} failed: {}",
+            args,
+            String::from_utf8_lossy(&output.stderr)
+        );
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    pub fn log(&self, entry: &str, date: &str) {
+        self.ok(&["log", entry, "--date", date]);
+    }
+
+    /// The entity names stored in `table`, sorted.
+    pub fn names(&self, table: &str) -> Vec<String> {
+        let conn = Connection::open(self.db_path()).unwrap();
+        let mut stmt = conn
+            .prepare(&format!("SELECT name FROM {table} ORDER BY name"))
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.map(Result::unwrap).collect()
+    }
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("1:1 with @Alice on %Dia #planning", "2025-01-10");
+    diary.log("Paired with @Alice and @Bob on %Dia #parser", "2025-03-02");
+    diary.log("@Alice reviewed %client/web", "2025-03-20");
+    diary.log("Solo on %Dia", "2025-03-21");
+    diary
+}
+
+#[test]
+fn person_dashboard_summarises_activity() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "person", "@alice", "--latest", "2"]);
+
+    assert!(out.starts_with("@Alice\n"));
+    assert!(out.contains("| First mention | 2025-01-10"));
+    assert!(out.contains("| Last mention  | 2025-03-20"));
+    assert!(out.contains("| Entries       | 3"));
+    // Quiet months are listed too.
+    assert!(out.contains("| 2025-02 |       0 |"));
+    assert!(out.contains("| 2025-03 |       2 |"));
+
+    assert!(out.contains("| @Bob        |       1 |"));
+    assert!(!out.contains("| @Alice  "));
+    assert!(out.contains("| %Dia          |       2 |"));
+
+    assert!(out.contains("@Alice reviewed"));
+    assert!(out.contains("Paired with"));
+    assert!(!out.contains("1:1 with"));
+}
+
+#[test]
+fn project_dashboard_includes_sub_projects_and_details() {
+    let diary = seeded();
+    diary.ok(&["entity", "set", "%client", "status=active"]);
+
+    let out = diary.ok(&["show", "project", "client"]);
+    assert!(out.starts_with("%client (status: active)\n"));
+    assert!(out.contains("| Entries       | 1"));
+    assert!(out.contains("@Alice reviewed %client/web"));
+    // Its own sub-projects are not listed as co-occurring.
+    assert!(!out.contains("With projects"));
+
+    diary.log("Moved %client/web styles into %Dia", "2025-03-22");
+    let out = diary.ok(&["show", "project", "client"]);
+    assert!(out.contains("| %Dia          |       1 |"));
+    assert!(!out.contains("| %client/web"));
+}
+
+#[test]
+fn resolves_aliases_and_rejects_unknown_names() {
+    let diary = seeded();
+    diary.ok(&["alias", "add", "#plan", "#planning"]);
+
+    let out = diary.ok(&["show", "tag", "plan"]);
+    assert!(out.starts_with("#planning\n"));
+    assert!(out.contains("1:1 with"));
+
+    let output = diary.run(&["show", "person", "Nobody"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("@Nobody not found"));
+}
@@ -0,0 +1,140 @@
+mod common;
+
+use chrono::{Datelike, Duration, Local, Weekday};
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("New year planning", "2025-01-01");
+    diary.log("Sprint in week fourteen", "2025-04-02");
+    diary.log("Mid-year review", "2025-06-30");
+    diary.log("Summer offsite", "2025-07-01");
+    diary
+}
+
+#[test]
+fn absolute_periods() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "entries", "--date", "2025-W14"]);
+    assert!(out.contains("Sprint in week fourteen"));
+    assert!(!out.contains("Mid-year"));
+
+    let out = diary.ok(&["show", "entries", "--date", "Q2 2025"]);
+    assert!(out.contains("Sprint in week fourteen"));
+    assert!(out.contains("Mid-year review"));
+    assert!(!out.contains("Summer offsite"));
+    assert!(!out.contains("New year"));
+
+    let out = diary.ok(&["show", "entries", "--date", "2025-q3"]);
+    assert!(out.contains("Summer offsite"));
+    assert!(!out.contains("Mid-year"));
+
+    let out = diary.ok(&["show", "entries", "--date", "june 2025"]);
+    assert!(out.contains("Mid-year review"));
+    assert!(!out.contains("Summer offsite"));
+
+    let out = diary.ok(&["show", "entries", "--date", "2025"]);
+    assert!(out.contains("New year planning"));
+    assert!(out.contains("Summer offsite"));
+}
+
+#[test]
+fn ranges_join_periods() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "entries", "--date", "2025-01..2025-W14"]);
+    assert!(out.contains("New year planning"));
+    assert!(out.contains("Sprint in week fourteen"));
+    assert!(!out.contains("Mid-year"));
+
+    let out = diary.ok(&["show", "entries", "--date", "from march 2025 to Q2 2025"]);
+    assert!(out.contains("Sprint in week fourteen"));
+    assert!(out.contains("Mid-year review"));
+    assert!(!out.contains("New year"));
+
+    assert!(!diary
+        .run(&["show", "entries", "--date", "2025-07..2025-06"])
+        .status
+        .success());
+}
+
+#[test]
+fn relative_days_when_logging() {
+    let diary = TestDiary::new();
+    let today = Local::now().date_naive();
+
+    diary.log("Entry for yesterday", "yesterday");
+    diary.log("Entry three days back", "3 days ago");
+    diary.log("Entry last friday", "last friday");
+
+    let yesterday = (today - Duration::days(1)).to_string();
+    let out = diary.ok(&["show", "entries", "--date", &yesterday]);
+    assert!(out.contains("Entry for yesterday"));
+
+    let three_days_ago = (today - Duration::days(3)).to_string();
+    let out = diary.ok(&["show", "entries", "--date", &three_days_ago]);
+    assert!(out.contains("Entry three days back"));
+
+    let mut last_friday = today - Duration::days(1);
+    while last_friday.weekday() != Weekday::Fri {
+        last_friday -= Duration::days(1);
+    }
+    let out = diary.ok(&["show", "entries", "--date", &last_friday.to_string()]);
+    assert!(out.contains("Entry last friday"));
+
+    let out = diary.ok(&["show", "entries", "--date", "last 7 days"]);
+    assert!(out.contains("Entry for yesterday"));
+    assert!(out.contains("Entry three days back"));
+}
+
+#[test]
+fn relative_periods() {
+    let diary = TestDiary::new();
+    let today = Local::now().date_naive();
+    let first_of_month = today.with_day(1).unwrap();
+    let last_month = first_of_month - Duration::days(1);
+
+    diary.log("This month", &first_of_month.to_string());
+    diary.log("Last month", &last_month.to_string());
+
+    let out = diary.ok(&["show", "entries", "--date", "this month"]);
+    assert!(out.contains("This month"));
+    assert!(!out.contains("Last month"));
+
+    let out = diary.ok(&["show", "entries", "--date", "last month"]);
+    assert!(out.contains("Last month"));
+    assert!(!out.contains("This month"));
+
+    let out = diary.ok(&["stats", "--period", "this month"]);
+    assert!(out.contains(&format!("Statistics for {}", first_of_month)));
+}
+
+#[test]
+fn log_requires_a_single_day() {
+    let diary = TestDiary::new();
+    assert!(!diary
+        .run(&["log", "Too vague", "--date", "this month"])
+        .status
+        .success());
+    assert!(!diary
+        .run(&["log", "Gibberish", "--date", "someday"])
+        .status
+        .success());
+}
+
+#[test]
+fn dates_out_of_range_are_errors() {
+    let diary = TestDiary::new();
+    for date in [
+        "400000000 years ago",
+        "4000000000 days ago",
+        "400000000 months ago",
+    ] {
+        let output = diary.run(&["log", "Long ago", "--date", date]);
+        assert_eq!(output.status.code(), Some(1), "{date}");
+        assert!(String::from_utf8_lossy(&output.stderr).contains("Date out of range"));
+    }
+    let output = diary.run(&["show", "entries", "--date", "last 400000000 years"]);
+    assert_eq!(output.status.code(), Some(1));
+}
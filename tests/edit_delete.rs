@@ -0,0 +1,109 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+fn count(diary: &TestDiary, sql: &str) -> i64 {
+    Connection::open(diary.db_path())
+        .unwrap()
+        .query_row(sql, [], |row| row.get(0))
+        .unwrap()
+}
+
+#[test]
+fn edit_relinks_entities_and_removes_orphans() {
+    let diary = TestDiary::new();
+    diary.log("Sync with @Jon on %Dia #meeting", "2025-04-01");
+    diary.log("Lunch with @Alice", "2025-04-01");
+
+    diary.ok(&["edit", "1", "Sync with @John on %Dia #meeting"]);
+
+    let people = diary.ok(&["show", "people"]);
+    assert!(people.contains("John"));
+    assert!(people.contains("Alice"));
+    assert!(!people.contains("Jon "));
+
+    let out = diary.ok(&["show", "entries", "--person", "John"]);
+    assert!(out.contains("Sync with @John"));
+    assert!(diary
+        .ok(&["show", "entries", "--person", "Jon"])
+        .contains("No entries found"));
+}
+
+#[test]
+fn edit_keeps_full_text_index_in_sync() {
+    let diary = TestDiary::new();
+    diary.log("Drafted the proposal", "2025-04-01");
+
+    diary.ok(&[
+        "edit",
+        "1",
+        "Rewrote the specification",
+        "--date",
+        "2025-04-02",
+    ]);
+
+    assert!(diary
+        .ok(&["show", "entries", "--search", "proposal"])
+        .contains("No entries found"));
+    let out = diary.ok(&["show", "entries", "--search", "specification"]);
+    assert!(out.contains("2025-04-02"));
+}
+
+#[test]
+fn edit_opens_editor_when_text_is_omitted() {
+    let diary = TestDiary::new();
+    diary.log("Call with @Jon", "2025-04-01");
+
+    let output = diary
+        .command()
+        .args(["edit", "1"])
+        .env_remove("VISUAL")
+        .env("EDITOR", "sed -i s/Jon/John/")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+
+    assert!(diary.ok(&["show", "entries"]).contains("Call with @John"));
+    assert!(!diary.ok(&["show", "people"]).contains("Jon "));
+}
+
+#[test]
+fn delete_removes_entry_links_and_orphans() {
+    let diary = TestDiary::new();
+    diary.log("Kickoff for %Dia with @Alice #planning", "2025-04-01");
+    diary.log("Follow-up on %Dia", "2025-04-02");
+
+    diary.ok(&["delete", "1", "--yes"]);
+
+    let out = diary.ok(&["show", "entries"]);
+    assert!(!out.contains("Kickoff"));
+    assert!(out.contains("Follow-up"));
+    assert!(diary.ok(&["show", "projects"]).contains("Dia"));
+    assert!(diary.ok(&["show", "people"]).contains("No people found."));
+    assert!(diary.ok(&["show", "tags"]).contains("No tags found."));
+    assert_eq!(count(&diary, "SELECT COUNT(*) FROM entry_people"), 0);
+    assert!(diary
+        .ok(&["show", "entries", "--search", "kickoff"])
+        .contains("No entries found"));
+}
+
+#[test]
+fn delete_without_confirmation_is_aborted() {
+    let diary = TestDiary::new();
+    diary.log("Keep me", "2025-04-01");
+
+    let out = diary.ok(&["delete", "1"]);
+    assert!(out.contains("Aborted."));
+    assert!(diary.ok(&["show", "entries"]).contains("Keep me"));
+}
+
+#[test]
+fn unknown_ids_are_reported() {
+    let diary = TestDiary::new();
+    let output = diary.run(&["delete", "42", "--yes"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("EntryNotFound(42)"));
+
+    assert!(!diary.run(&["edit", "42", "text"]).status.success());
+}
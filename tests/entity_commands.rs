@@ -0,0 +1,83 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Call with @JohnK about %Dia", "2025-03-01");
+    diary.log("Lunch with @john-k and @JohnKay", "2025-03-02");
+    diary.log("Review with @John and @JohnK", "2025-03-03");
+    diary
+}
+
+#[test]
+fn rename_keeps_links_and_optionally_rewrites_text() {
+    let diary = seeded();
+    let out = diary.ok(&["entity", "rename", "%Dia", "DiaCLI", "--rewrite-text"]);
+    assert!(out.contains("Renamed %Dia to %DiaCLI."));
+    assert!(out.contains("Updated the text of 1 entries."));
+
+    assert_eq!(diary.names("projects"), vec!["DiaCLI"]);
+    let out = diary.ok(&["show", "entries", "--project", "DiaCLI"]);
+    assert!(out.contains("Call with @JohnK about %DiaCLI"));
+    // The FTS index follows the rewritten text.
+    let out = diary.ok(&["show", "entries", "--search", "DiaCLI"]);
+    assert!(out.contains("Call with"));
+}
+
+#[test]
+fn rename_refuses_existing_name() {
+    let diary = seeded();
+    let output = diary.run(&["entity", "rename", "@JohnK", "@John"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("entity merge"));
+}
+
+#[test]
+fn merge_moves_links_without_duplicates() {
+    let diary = seeded();
+    diary.ok(&["entity", "merge", "@JohnK", "@John"]);
+    diary.ok(&["entity", "merge", "@john-k", "John"]);
+
+    assert_eq!(diary.names("people"), vec!["John", "JohnKay"]);
+    let out = diary.ok(&["show", "entries", "--person", "John"]);
+    assert!(out.contains("Call with @JohnK"));
+    assert!(out.contains("Lunch with @john-k"));
+    assert!(out.contains("Review with"));
+
+    let conn = Connection::open(diary.db_path()).unwrap();
+    let links: i64 = conn
+        .query_row(
+            "SELECT COUNT(*) FROM entry_people WHERE entry_id = 3",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(links, 1);
+}
+
+#[test]
+fn merge_rewrites_only_exact_markers() {
+    let diary = seeded();
+    let out = diary.ok(&["entity", "merge", "@JohnK", "@John", "--rewrite-text"]);
+    assert!(out.contains("Updated the text of 2 entries."));
+
+    let out = diary.ok(&["show", "entries", "--format", "csv"]);
+    assert!(out.contains("Call with @John about %Dia"));
+    assert!(out.contains("Lunch with @john-k and @JohnKay"));
+    assert!(out.contains("Review with @John and @John"));
+}
+
+#[test]
+fn references_need_matching_markers() {
+    let diary = seeded();
+    for args in [
+        ["entity", "merge", "JohnK", "John"],
+        ["entity", "merge", "@JohnK", "%Dia"],
+        ["entity", "merge", "@Nobody", "@John"],
+    ] {
+        let output = diary.run(&args);
+        assert!(!output.status.success(), "{:?} succeeded", args);
+    }
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Planning %Dia with @Alice", "2025-03-01");
+    diary.log("Wrapped up %Legacy", "2025-03-02");
+    diary
+}
+
+#[test]
+fn fields_show_up_in_show_output() {
+    let diary = seeded();
+    let out = diary.ok(&[
+        "entity",
+        "set",
+        "%Dia",
+        "status=active",
+        "description=Work diary CLI",
+        "client=ACME",
+    ]);
+    assert!(out.contains("Updated %Dia."));
+
+    let out = diary.ok(&["show", "projects"]);
+    assert!(out.contains("client: ACME, description: Work diary CLI, status: active"));
+
+    let out = diary.ok(&["show", "projects", "--format", "csv"]);
+    assert!(out.contains("id,name,entries,first_seen,last_used,archived,metadata"));
+    assert!(out.contains(
+        "Dia,1,2025-03-01,2025-03-01,false,client=ACME;description=Work diary CLI;status=active"
+    ));
+
+    // An empty value removes a field.
+    diary.ok(&["entity", "set", "%dia", "client="]);
+    let out = diary.ok(&["show", "projects"]);
+    assert!(!out.contains("ACME"));
+    assert!(out.contains("description: Work diary CLI, status: active"));
+}
+
+#[test]
+fn archived_entities_are_hidden_until_restored() {
+    let diary = seeded();
+    diary.ok(&["entity", "set", "%Legacy", "--archived", "status=done"]);
+
+    let out = diary.ok(&["show", "projects"]);
+    assert!(out.contains("Dia"));
+    assert!(!out.contains("Legacy"));
+    let out = diary.ok(&["show", "projects", "--all"]);
+    assert!(out.contains("status: done, archived"));
+
+    // Entries still mention it.
+    let out = diary.ok(&["show", "entries", "--project", "Legacy"]);
+    assert!(out.contains("Wrapped up"));
+
+    diary.ok(&["entity", "set", "%Legacy", "--unarchive"]);
+    assert!(diary.ok(&["show", "projects"]).contains("Legacy"));
+}
+
+#[test]
+fn metadata_survives_edits_merges_and_export_round_trips() {
+    let diary = seeded();
+    diary.ok(&["entity", "set", "@Alice", "role=Designer"]);
+
+    // Editing away the last mention keeps an entity that has metadata.
+    diary.ok(&["edit", "1", "Planning %Dia"]);
+    assert!(diary.ok(&["show", "people"]).contains("role: Designer"));
+
+    diary.log("Coffee with @Ali", "2025-03-03");
+    diary.ok(&["entity", "set", "@Ali", "role=Intern", "team=Core"]);
+    diary.ok(&["entity", "merge", "@Ali", "@Alice"]);
+    let out = diary.ok(&["show", "people"]);
+    assert!(out.contains("role: Designer, team: Core"));
+
+    let markdown = diary.ok(&["export"]);
+    assert!(markdown.contains("**@Alice** (role: Designer, team: Core)"));
+
+    let archive = diary.path().join("backup.json");
+    diary.ok(&[
+        "export",
+        "--format",
+        "json",
+        "--output",
+        archive.to_str().unwrap(),
+    ]);
+    let restored = TestDiary::new();
+    restored.ok(&["import", archive.to_str().unwrap()]);
+    assert!(restored
+        .ok(&["show", "people"])
+        .contains("role: Designer, team: Core"));
+}
+
+#[test]
+fn rejects_bad_input() {
+    let diary = seeded();
+    assert!(!diary
+        .run(&["entity", "set", "%Nope", "a=b"])
+        .status
+        .success());
+    assert!(!diary
+        .run(&["entity", "set", "%Dia", "novalue"])
+        .status
+        .success());
+    assert!(!diary
+        .run(&["entity", "set", "%Dia", "bad key=1"])
+        .status
+        .success());
+    assert!(!diary.run(&["entity", "set", "%Dia"]).status.success());
+}
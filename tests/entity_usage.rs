@@ -0,0 +1,70 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Kickoff with @Alice on %Dia", "2025-01-10");
+    diary.log("Paired with @Alice and @Bob on %client/web", "2025-03-02");
+    diary.log("@Alice reviewed %Dia", "2025-03-20");
+    diary.log("Sketches with @Carol", "2025-02-01");
+    diary
+}
+
+/// The first column of each table row, header included.
+fn names(out: &str) -> Vec<String> {
+    out.lines()
+        .filter(|line| line.starts_with("| "))
+        .map(|line| line.split('|').nth(1).unwrap().trim().to_string())
+        .collect()
+}
+
+#[test]
+fn lists_counts_and_dates() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "people"]);
+    assert!(out.contains("| people | entries | first seen | last used  |"));
+    assert!(out.contains("| Alice  |       3 | 2025-01-10 | 2025-03-20 |"));
+    assert!(out.contains("| Carol  |       1 | 2025-02-01 | 2025-02-01 |"));
+}
+
+#[test]
+fn sorts_by_count_and_recency() {
+    let diary = seeded();
+    assert_eq!(
+        names(&diary.ok(&["show", "people", "--sort", "count"])),
+        ["people", "Alice", "Bob", "Carol"]
+    );
+    assert_eq!(
+        names(&diary.ok(&["show", "people", "--sort", "recent"])),
+        ["people", "Alice", "Bob", "Carol"]
+    );
+    // Sorted listings show full names rather than a tree; the parent has no
+    // entries of its own and sorts last.
+    assert_eq!(
+        names(&diary.ok(&["show", "projects", "--sort", "recent"])),
+        ["projects", "Dia", "client/web", "client"]
+    );
+}
+
+#[test]
+fn filters_by_recent_use() {
+    let diary = seeded();
+    assert_eq!(
+        names(&diary.ok(&["show", "people", "--since", "2025-03-01"])),
+        ["people", "Alice", "Bob"]
+    );
+    assert_eq!(
+        names(&diary.ok(&["show", "people", "--since", "2025-03-01", "--unused"])),
+        ["people", "Carol"]
+    );
+
+    // Structural parents are never reported as unused.
+    assert!(diary
+        .ok(&["show", "projects", "--unused"])
+        .contains("No projects found."));
+    assert_eq!(
+        names(&diary.ok(&["show", "projects", "--unused", "--since", "2025-03-10"])),
+        ["projects", "client/web"]
+    );
+}
@@ -0,0 +1,115 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Paired with @Alice on %Dia #parser", "2025-03-01");
+    diary.log("Website 50% done, see %Website", "2025-03-02");
+    diary.log("Lunch <outside>", "2025-03-03");
+    diary
+}
+
+#[test]
+fn markdown_groups_by_day_and_links_entities() {
+    let diary = seeded();
+    let out = diary.ok(&["export"]);
+
+    assert!(out.starts_with("# Diary\n"));
+    assert!(out.contains("2025-03-01 (Saturday)"));
+    assert!(out.contains(
+        "- Paired with [@Alice](#person-alice) on [%Dia](#project-dia) [#parser](#tag-parser)"
+    ));
+    // Stray marker characters are left as they are.
+    assert!(out.contains("- Website 50% done, see [%Website](#project-website)"));
+    assert!(
+        out.contains("<a id=\"person-alice\"></a>**@Alice**: [2025-03-01](#section-2025-03-01)")
+    );
+}
+
+#[test]
+fn markdown_groups_by_project_with_unassigned_last() {
+    let diary = seeded();
+    let out = diary.ok(&["export", "--group-by", "project"]);
+
+    let dia = out.find("%Dia\n").unwrap();
+    let website = out.find("%Website\n").unwrap();
+    let unassigned = out.find("No project\n").unwrap();
+    assert!(dia < website && website < unassigned);
+    assert!(out.contains("- **2025-03-03**: Lunch \\<outside\\>"));
+}
+
+#[test]
+fn date_range_limits_entries() {
+    let diary = seeded();
+    let out = diary.ok(&["export", "--from", "2025-03-02", "--to", "2025-03-02"]);
+
+    assert!(out.starts_with("# Diary 2025-03-02 to 2025-03-02\n"));
+    assert!(out.contains("Website"));
+    assert!(!out.contains("Alice"));
+    assert!(!out.contains("Lunch"));
+}
+
+#[test]
+fn reversed_date_range_is_an_error() {
+    let diary = seeded();
+    let output = diary.run(&["export", "--from", "2025-03-03", "--to", "2025-03-01"]);
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Invalid date range: 2025-03-03 is after 2025-03-01"));
+}
+
+#[test]
+fn markdown_escapes_content() {
+    let diary = TestDiary::new();
+    diary.log(
+        "# not a heading, [draft] *not* bold with @Alice",
+        "2025-03-01",
+    );
+    let out = diary.ok(&["export"]);
+
+    assert!(out
+        .contains("- \\# not a heading, \\[draft\\] \\*not\\* bold with [@Alice](#person-alice)"));
+}
+
+#[test]
+fn entity_names_are_escaped_and_anchors_unique() {
+    let diary = TestDiary::new();
+    diary.log(
+        "Met @\"Anna Maria\" and @Anna_Maria about %my_app",
+        "2025-03-01",
+    );
+    let out = diary.ok(&["export"]);
+
+    assert!(
+        out.contains("[@Anna Maria](#person-anna-maria) and [@Anna\\_Maria](#person-anna-maria-2)")
+    );
+    assert!(out.contains("<a id=\"person-anna-maria-2\"></a>**@Anna\\_Maria**"));
+    assert!(out.contains("<a id=\"project-my-app\"></a>**%my\\_app**"));
+
+    let out = diary.ok(&["export", "--format", "html"]);
+    assert!(out.contains("<li id=\"person-anna-maria-2\">"));
+}
+
+#[test]
+fn html_escapes_content_and_renders_badges() {
+    let diary = seeded();
+    let out = diary.ok(&["export", "--format", "html"]);
+
+    assert!(out.starts_with("<!DOCTYPE html>"));
+    assert!(out.contains("<a class=\"badge person\" href=\"#person-alice\">@Alice</a>"));
+    assert!(out.contains("Lunch &lt;outside&gt;"));
+    assert!(out.contains("<li id=\"tag-parser\">"));
+}
+
+#[test]
+fn writes_to_output_file() {
+    let diary = seeded();
+    let path = diary.path().join("diary.md");
+    let out = diary.ok(&["export", "-o", path.to_str().unwrap()]);
+
+    assert!(out.contains("Exported 3 entries"));
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert!(written.contains("## Index"));
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log(
+        "Fixed %client/website/frontend #bug/regression",
+        "2025-03-01",
+    );
+    diary.log("Call about %client/mobile and %other", "2025-03-02");
+    diary.log("Planning for %client, filed a #bug", "2025-03-03");
+    diary
+}
+
+#[test]
+fn filters_include_nested_entities() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "entries", "--project", "client"]);
+    assert!(out.contains("Fixed"));
+    assert!(out.contains("Call about"));
+    assert!(out.contains("Planning"));
+
+    let out = diary.ok(&["show", "entries", "--project", "%client/website"]);
+    assert!(out.contains("Fixed"));
+    assert!(!out.contains("Call about"));
+
+    let out = diary.ok(&["show", "entries", "--tag", "bug"]);
+    assert!(out.contains("Fixed"));
+    assert!(out.contains("Planning"));
+    let out = diary.ok(&["show", "entries", "--tag", "bug/regression"]);
+    assert!(!out.contains("Planning"));
+}
+
+#[test]
+fn parents_are_created_and_shown_as_a_tree() {
+    let diary = seeded();
+    assert_eq!(
+        diary.names("projects"),
+        vec![
+            "client",
+            "client/mobile",
+            "client/website",
+            "client/website/frontend",
+            "other"
+        ]
+    );
+
+    let out = diary.ok(&["show", "projects"]);
+    let lines = out
+        .lines()
+        .filter(|line| line.starts_with("| "))
+        .map(|line| line.split('|').nth(1).unwrap().trim())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "projects",
+            "client",
+            "├── mobile",
+            "└── website",
+            "└── frontend",
+            "other"
+        ]
+    );
+
+    // Machine-readable output keeps full names.
+    let out = diary.ok(&["show", "projects", "--format", "csv"]);
+    assert!(out.contains("client/website/frontend"));
+}
+
+#[test]
+fn stats_roll_up_to_parents() {
+    let diary = seeded();
+
+    let out = diary.ok(&["stats"]);
+    assert!(out.contains("| client                  |       1 |"));
+
+    let out = diary.ok(&["stats", "--rollup"]);
+    assert!(out.contains("| client                  |       3 |"));
+    assert!(out.contains("| client  | 75.0% |"));
+    assert!(out.contains("| bug            |       2 |"));
+}
+
+#[test]
+fn renaming_and_merging_move_nested_entities() {
+    let diary = seeded();
+    diary.ok(&["entity", "rename", "%client", "acme", "--rewrite-text"]);
+    assert_eq!(
+        diary.names("projects"),
+        vec![
+            "acme",
+            "acme/mobile",
+            "acme/website",
+            "acme/website/frontend",
+            "other"
+        ]
+    );
+    let out = diary.ok(&["show", "entries", "--project", "acme"]);
+    assert!(out.contains("Fixed %acme/website/frontend"));
+
+    diary.ok(&["entity", "merge", "%acme/website", "acme", "--rewrite-text"]);
+    assert_eq!(
+        diary.names("projects"),
+        vec!["acme", "acme/frontend", "acme/mobile", "other"]
+    );
+    let out = diary.ok(&["show", "entries", "--project", "acme/frontend"]);
+    assert!(out.contains("Fixed %acme/frontend"));
+
+    let output = diary.run(&["entity", "merge", "%acme", "acme/mobile"]);
+    assert!(!output.status.success());
+    let output = diary.run(&["entity", "rename", "%acme", "acme/new"]);
+    assert!(!output.status.success());
+}
+
+#[test]
+fn deleting_the_last_entry_removes_unused_parents() {
+    let diary = TestDiary::new();
+    diary.log("Only %client/website", "2025-03-01");
+    assert_eq!(diary.names("projects"), vec!["client", "client/website"]);
+
+    diary.ok(&["delete", "1", "--yes"]);
+    assert!(diary.names("projects").is_empty());
+}
@@ -0,0 +1,149 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+fn contents(diary: &TestDiary) -> Vec<(String, String)> {
+    let conn = Connection::open(diary.db_path()).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT date, content FROM entries ORDER BY date, id")
+        .unwrap();
+    let rows = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .unwrap();
+    rows.map(Result::unwrap).collect()
+}
+
+#[test]
+fn jrnl_entries_are_split_on_timestamps() {
+    let diary = TestDiary::new();
+    let path = diary.path().join("journal.txt");
+    std::fs::write(
+        &path,
+        "[2025-03-01 09:15] Planning with @Alice.\nDrafted the %Dia roadmap #planning\n\n\
+         [2025-03-02 04:30:00 PM] Short one\n\
+         2025-03-03 08:00 Unbracketed entry\n",
+    )
+    .unwrap();
+
+    let out = diary.ok(&["import", "--from", "jrnl", path.to_str().unwrap()]);
+    assert!(out.contains("Imported 3 entries"));
+
+    let entries = contents(&diary);
+    assert_eq!(
+        entries[0],
+        (
+            "2025-03-01".to_string(),
+            "Planning with @Alice.\nDrafted the %Dia roadmap #planning".to_string()
+        )
+    );
+    assert_eq!(entries[1].1, "Short one");
+    assert_eq!(entries[2].0, "2025-03-03");
+
+    let out = diary.ok(&["show", "entries", "--person", "Alice", "--tag", "planning"]);
+    assert!(out.contains("Planning with"));
+}
+
+#[test]
+fn dayone_tags_become_markers() {
+    let diary = TestDiary::new();
+    let path = diary.path().join("Journal.json");
+    std::fs::write(
+        &path,
+        r#"{"metadata": {"version": "1.0"}, "entries": [
+            {"creationDate": "2025-03-01T12:00:00Z", "text": "Release 1\\.2 with @Bob", "tags": ["work", "release day"]},
+            {"creationDate": "2025-03-02T12:00:00Z", "text": "Already tagged #work", "tags": ["work"]},
+            {"creationDate": "2025-03-02T13:00:00Z", "text": "Ran the #workshop with #Team", "tags": ["work", "team"]},
+            {"creationDate": "2025-03-03T12:00:00Z", "text": ""}
+        ]}"#,
+    )
+    .unwrap();
+
+    diary.ok(&["import", "--from", "dayone", path.to_str().unwrap()]);
+
+    let entries = contents(&diary);
+    assert_eq!(entries.len(), 3);
+    assert_eq!(entries[0].1, "Release 1.2 with @Bob #work #release-day");
+    assert_eq!(entries[1].1, "Already tagged #work");
+    assert_eq!(entries[2].1, "Ran the #workshop with #Team #work");
+
+    let conn = Connection::open(diary.db_path()).unwrap();
+    let created_at: String = conn
+        .query_row("SELECT created_at FROM entries WHERE id = 1", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(created_at, "2025-03-01 12:00:00");
+}
+
+#[test]
+fn daily_notes_split_into_items_and_map_links() {
+    let diary = TestDiary::new();
+    let notes = diary.path().join("vault");
+    std::fs::create_dir_all(notes.join("2025")).unwrap();
+    std::fs::write(
+        notes.join("2025").join("2025-03-01.md"),
+        "---\ntags: daily\n---\n# Saturday\n\n- Call with @Carol about [[Big Co|the client]]\n  \
+         follow-up next week\n- [x] Reviewed PR #review\n\nLoose paragraph.\n",
+    )
+    .unwrap();
+    std::fs::write(notes.join("2025-03-02.md"), "* Worked on [[Dia]]\n").unwrap();
+    std::fs::write(notes.join("README.md"), "- not a daily note\n").unwrap();
+
+    diary.ok(&[
+        "import",
+        "--from",
+        "daily-notes",
+        "--links-as-projects",
+        notes.to_str().unwrap(),
+    ]);
+
+    let entries = contents(&diary)
+        .into_iter()
+        .map(|(date, content)| format!("{date} {content}"))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        entries,
+        vec![
+            "2025-03-01 Call with @Carol about %Big-Co\nfollow-up next week",
+            "2025-03-01 Reviewed PR #review",
+            "2025-03-01 Loose paragraph.",
+            "2025-03-02 Worked on %Dia",
+        ]
+    );
+    assert!(diary.ok(&["show", "projects"]).contains("Big-Co"));
+}
+
+#[test]
+fn dry_run_previews_without_writing() {
+    let diary = TestDiary::new();
+    diary.log("Short one", "2025-03-02");
+    let path = diary.path().join("journal.txt");
+    std::fs::write(
+        &path,
+        "[2025-03-01 09:15] New entry\n[2025-03-02 10:00] Short one\n",
+    )
+    .unwrap();
+
+    let out = diary.ok(&[
+        "import",
+        "--from",
+        "jrnl",
+        "--dry-run",
+        path.to_str().unwrap(),
+    ]);
+    assert!(out.contains("New entry"));
+    assert!(out.contains("Dry run: would import 1 entries (1 duplicates skipped)."));
+    assert_eq!(contents(&diary).len(), 1);
+}
+
+#[test]
+fn malformed_jrnl_is_rejected() {
+    let diary = TestDiary::new();
+    let path = diary.path().join("journal.txt");
+    std::fs::write(&path, "Just some text\n").unwrap();
+
+    let output = diary.run(&["import", "--from", "jrnl", path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Line 1"));
+}
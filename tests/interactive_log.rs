@@ -0,0 +1,90 @@
+mod common;
+
+use common::TestDiary;
+use std::io::Write;
+use std::process::Stdio;
+
+fn log_session(diary: &TestDiary, args: &[&str], input: &str) -> String {
+    let mut child = diary
+        .command()
+        .arg("log")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn logs_several_entries_in_one_session() {
+    let diary = TestDiary::new();
+    let out = log_session(
+        &diary,
+        &["--date", "2025-05-01"],
+        "Reviewed PR for %Dia with @Alice\n\nPlanned #release\n",
+    );
+    assert!(out.contains("Logged 2 entries."));
+
+    let out = diary.ok(&["show", "entries", "--date", "2025-05-01"]);
+    assert!(out.contains("Reviewed PR for %Dia with @Alice"));
+    assert!(out.contains("Planned #release"));
+    assert!(diary.ok(&["show", "people"]).contains("Alice"));
+}
+
+#[test]
+fn backslash_continues_an_entry() {
+    let diary = TestDiary::new();
+    let out = log_session(
+        &diary,
+        &[],
+        "First line of %Dia notes\\\nsecond line #multi\n",
+    );
+    assert!(out.contains("Logged 1 entry."));
+
+    let out = diary.ok(&["show", "entries", "--tag", "multi"]);
+    assert!(out.contains("First line of %Dia notes"));
+    assert!(out.contains("second line #multi"));
+    assert!(!out.contains('\\'));
+}
+
+#[test]
+fn invalid_date_is_rejected_before_the_prompt() {
+    let diary = TestDiary::new();
+    let mut child = diary
+        .command()
+        .args(["log", "--date", "garbage"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"Important entry\n")
+        .ok();
+    let output = child.wait_with_output().unwrap();
+
+    assert!(!output.status.success());
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("dia>"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("Logging entries"));
+}
+
+#[test]
+fn keeps_a_history_file() {
+    let diary = TestDiary::new();
+    log_session(&diary, &[], "Remember this entry\n");
+
+    let history = std::fs::read_to_string(diary.path().join("dia").join("history.txt")).unwrap();
+    assert!(history.contains("Remember this entry"));
+}
@@ -0,0 +1,209 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+/// Schema historically created by `Core::init_tables`.
+const CORE_SCHEMA: &str = r#"
+    CREATE TABLE entries (
+        id INTEGER PRIMARY KEY,
+        content TEXT NOT NULL,
+        date DATE NOT NULL,
+        created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+    );
+    CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);
+    CREATE TABLE projects (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);
+    CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE, created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP);
+    CREATE TABLE entry_people (entry_id INTEGER NOT NULL REFERENCES entries(id), person_id INTEGER NOT NULL REFERENCES people(id), PRIMARY KEY (entry_id, person_id));
+    CREATE TABLE entry_projects (entry_id INTEGER NOT NULL REFERENCES entries(id), project_id INTEGER NOT NULL REFERENCES projects(id), PRIMARY KEY (entry_id, project_id));
+    CREATE TABLE entry_tags (entry_id INTEGER NOT NULL REFERENCES entries(id), tag_id INTEGER NOT NULL REFERENCES tags(id), PRIMARY KEY (entry_id, tag_id));
+    CREATE VIRTUAL TABLE entries_fts USING fts5(content, tokenize = 'porter unicode61');
+"#;
+
+/// Schema historically created by `db::init_db`.
+const DB_SCHEMA: &str = r#"
+    CREATE TABLE entries (id INTEGER PRIMARY KEY, date TEXT NOT NULL, content TEXT NOT NULL, last_reviewed TEXT);
+    CREATE TABLE projects (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+    CREATE TABLE tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+    CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+    CREATE TABLE entry_projects (entry_id INTEGER, project_id INTEGER, PRIMARY KEY (entry_id, project_id),
+        FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
+        FOREIGN KEY (project_id) REFERENCES projects (id) ON DELETE CASCADE);
+    CREATE TABLE entry_tags (entry_id INTEGER, tag_id INTEGER, PRIMARY KEY (entry_id, tag_id),
+        FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
+        FOREIGN KEY (tag_id) REFERENCES tags (id) ON DELETE CASCADE);
+    CREATE TABLE entry_people (entry_id INTEGER, person_id INTEGER, PRIMARY KEY (entry_id, person_id),
+        FOREIGN KEY (entry_id) REFERENCES entries (id) ON DELETE CASCADE,
+        FOREIGN KEY (person_id) REFERENCES people (id) ON DELETE CASCADE);
+    CREATE VIRTUAL TABLE entries_fts USING fts5(content, content='entries', content_rowid='id');
+    CREATE TRIGGER entries_ai AFTER INSERT ON entries BEGIN
+        INSERT INTO entries_fts(rowid, content) VALUES (new.id, new.content);
+    END;
+    CREATE INDEX idx_entries_date ON entries (date);
+    CREATE INDEX idx_people_name ON people (name);
+"#;
+
+fn legacy_diary(schema: &str, data: &str) -> TestDiary {
+    let diary = TestDiary::new();
+    std::fs::create_dir_all(diary.db_path().parent().unwrap()).unwrap();
+    let conn = Connection::open(diary.db_path()).unwrap();
+    conn.execute_batch(schema).unwrap();
+    conn.execute_batch(data).unwrap();
+    diary
+}
+
+fn open(diary: &TestDiary) -> Connection {
+    Connection::open(diary.db_path()).unwrap()
+}
+
+#[test]
+fn fresh_database_is_created_at_latest_version() {
+    let diary = TestDiary::new();
+    diary.log("First entry with @Alice", "2025-01-01");
+
+    let conn = open(&diary);
+    let version: i64 = conn
+        .query_row("PRAGMA user_version", [], |row| row.get(0))
+        .unwrap();
+    assert!(version >= 1);
+
+    let last_reviewed: Option<String> = conn
+        .query_row("SELECT last_reviewed FROM entries", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(last_reviewed, None);
+}
+
+#[test]
+fn upgrades_core_schema_without_data_loss() {
+    let diary = legacy_diary(
+        CORE_SCHEMA,
+        r#"
+        INSERT INTO entries (id, content, date, created_at)
+            VALUES (7, 'Planned %Dia with @Alice #roadmap', '2024-05-01', '2024-05-01 09:30:00');
+        INSERT INTO entries_fts (rowid, content) VALUES (7, 'Planned %Dia with @Alice #roadmap');
+        INSERT INTO people (id, name) VALUES (1, 'Alice');
+        INSERT INTO projects (id, name) VALUES (1, 'Dia');
+        INSERT INTO tags (id, name) VALUES (1, 'roadmap');
+        INSERT INTO entry_people VALUES (7, 1);
+        INSERT INTO entry_projects VALUES (7, 1);
+        INSERT INTO entry_tags VALUES (7, 1);
+        "#,
+    );
+
+    let out = diary.ok(&[
+        "show", "entries", "--person", "Alice", "--search", "planned",
+    ]);
+    assert!(out.contains("Planned %Dia with @Alice #roadmap"));
+    assert!(diary.ok(&["show", "tags"]).contains("roadmap"));
+
+    let conn = open(&diary);
+    let created_at: String = conn
+        .query_row("SELECT created_at FROM entries WHERE id = 7", [], |row| {
+            row.get(0)
+        })
+        .unwrap();
+    assert_eq!(created_at, "2024-05-01 09:30:00");
+}
+
+#[test]
+fn upgrades_db_schema_without_data_loss() {
+    let diary = legacy_diary(
+        DB_SCHEMA,
+        r#"
+        INSERT INTO entries (id, date, content, last_reviewed)
+            VALUES (3, '2024-06-02', 'Deployed %Website for @Bob', '2024-06-10');
+        INSERT INTO people (id, name) VALUES (4, 'Bob');
+        INSERT INTO projects (id, name) VALUES (5, 'Website');
+        INSERT INTO entry_people VALUES (3, 4);
+        INSERT INTO entry_projects VALUES (3, 5);
+        "#,
+    );
+
+    let out = diary.ok(&[
+        "show",
+        "entries",
+        "--project",
+        "Website",
+        "--search",
+        "deployed",
+    ]);
+    assert!(out.contains("Deployed %Website for @Bob"));
+
+    let conn = open(&diary);
+    let last_reviewed: Option<String> = conn
+        .query_row(
+            "SELECT last_reviewed FROM entries WHERE id = 3",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(last_reviewed.as_deref(), Some("2024-06-10"));
+}
+
+#[test]
+fn new_entries_after_upgrade_are_searchable_and_cascade() {
+    let diary = legacy_diary(
+        CORE_SCHEMA,
+        "INSERT INTO entries (id, content, date) VALUES (1, 'Old note', '2024-01-01');",
+    );
+    diary.log("Fresh note about %Dia", "2025-02-02");
+
+    let out = diary.ok(&["show", "entries", "--search", "fresh"]);
+    assert!(out.contains("Fresh note about %Dia"));
+
+    let conn = open(&diary);
+    conn.execute_batch("PRAGMA foreign_keys = ON; DELETE FROM entries;")
+        .unwrap();
+    let links: i64 = conn
+        .query_row("SELECT COUNT(*) FROM entry_projects", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(links, 0);
+}
+
+#[test]
+fn refuses_databases_from_newer_versions() {
+    let diary = legacy_diary(CORE_SCHEMA, "PRAGMA user_version = 9999;");
+    assert!(!diary.run(&["show", "entries"]).status.success());
+}
+
+#[test]
+fn nested_names_get_parent_links() {
+    let diary = legacy_diary(
+        CORE_SCHEMA,
+        r#"
+        INSERT INTO entries (id, content, date) VALUES (1, 'Shipped %"client/web"', '2025-01-01');
+        INSERT INTO projects (id, name) VALUES (1, 'client/web');
+        INSERT INTO entry_projects VALUES (1, 1);
+        "#,
+    );
+
+    let out = diary.ok(&["show", "entries", "--project", "client"]);
+    assert!(out.contains("Shipped"));
+
+    let parent: String = open(&diary)
+        .query_row(
+            "SELECT p.name FROM projects c JOIN projects p ON p.id = c.parent_id
+             WHERE c.name = 'client/web'",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert_eq!(parent, "client");
+}
+
+#[test]
+fn existing_entries_get_their_tracked_minutes() {
+    let diary = legacy_diary(
+        CORE_SCHEMA,
+        r#"
+        INSERT INTO entries (id, content, date) VALUES (1, 'Call ~1h15m', '2025-01-01');
+        INSERT INTO entries (id, content, date) VALUES (2, 'No time noted', '2025-01-01');
+        INSERT INTO entries (id, content, date) VALUES (3, 'Forever ~99999999999999999999h', '2025-01-01');
+        "#,
+    );
+
+    let out = diary.ok(&["report", "time", "--period", "2025-01"]);
+    assert!(out.contains("| 2025-01-01 | 1h 15m | Call ~1h15m |"));
+    assert!(!out.contains("No time noted"));
+    assert!(!out.contains("Forever"));
+}
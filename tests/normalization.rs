@@ -0,0 +1,118 @@
+mod common;
+
+use common::TestDiary;
+
+/// A diary whose `config.toml` carries the given `[entities]` settings.
+fn configured(entities: &str) -> TestDiary {
+    let diary = TestDiary::new();
+    let dir = diary.path().join("dia");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("config.toml"),
+        format!(
+            "diary_db_path = {:?}\n\n[entities]\n{}\n",
+            diary.db_path().to_str().unwrap(),
+            entities
+        ),
+    )
+    .unwrap();
+    diary
+}
+
+#[test]
+fn names_differing_in_case_are_one_entity() {
+    let diary = TestDiary::new();
+    diary.log("Found a #Bug in %Dia", "2025-03-01");
+    diary.log("Another #bug in %dia", "2025-03-02");
+
+    assert_eq!(diary.names("tags"), vec!["Bug"]);
+    assert_eq!(diary.names("projects"), vec!["Dia"]);
+    let out = diary.ok(&["show", "entries", "--tag", "BUG"]);
+    assert!(out.contains("Found a"));
+    assert!(out.contains("Another"));
+}
+
+#[test]
+fn composed_and_decomposed_accents_match() {
+    let diary = TestDiary::new();
+    diary.log("Call with @Jos\u{e9}", "2025-03-01");
+    diary.log("Lunch with @Jose\u{301}", "2025-03-02");
+
+    assert_eq!(diary.names("people"), vec!["Jos\u{e9}"]);
+}
+
+#[test]
+fn punctuation_and_quoted_names() {
+    let diary = TestDiary::new();
+    diary.log(
+        "Fixed @O'Brien's laptop, met @J.R.R. and @\"Anna Maria\" about %v1.2.",
+        "2025-03-01",
+    );
+
+    assert_eq!(
+        diary.names("people"),
+        vec!["Anna Maria", "J.R.R", "O'Brien"]
+    );
+    assert_eq!(diary.names("projects"), vec!["v1.2"]);
+
+    let out = diary.ok(&["show", "entries", "--person", "anna maria"]);
+    assert!(out.contains("Fixed"));
+    let out = diary.ok(&["show", "entries", "--person", "@\"Anna Maria\""]);
+    assert!(out.contains("Fixed"));
+}
+
+#[test]
+fn renaming_to_a_multi_word_name_quotes_the_marker() {
+    let diary = TestDiary::new();
+    diary.log("Sync with @Anna", "2025-03-01");
+    diary.ok(&[
+        "entity",
+        "rename",
+        "@Anna",
+        "@\"Anna Maria\"",
+        "--rewrite-text",
+    ]);
+
+    let out = diary.ok(&["show", "entries", "--format", "csv"]);
+    assert!(out.contains(r#"Sync with @""Anna Maria"""#));
+    assert_eq!(diary.names("people"), vec!["Anna Maria"]);
+
+    // Changing only the case is a rename, not a clash.
+    diary.ok(&["entity", "rename", "@anna maria", "@\"ANNA MARIA\""]);
+    assert_eq!(diary.names("people"), vec!["ANNA MARIA"]);
+}
+
+#[test]
+fn policy_is_configurable() {
+    let diary = configured("case_folding = false\nallowed_punctuation = \"\"");
+    diary.log("Found a #Bug with @O'Brien", "2025-03-01");
+    diary.log("Another #bug", "2025-03-02");
+
+    assert_eq!(diary.names("tags"), vec!["Bug", "bug"]);
+    assert_eq!(diary.names("people"), vec!["O"]);
+    let out = diary.ok(&["show", "entries", "--tag", "bug"]);
+    assert!(!out.contains("Found a"));
+}
+
+#[test]
+fn aliases_follow_the_case_policy() {
+    let diary = configured("case_folding = false");
+    diary.ok(&["alias", "add", "@Jk", "@JohnK"]);
+    diary.ok(&["alias", "add", "@jk", "@Jane"]);
+    diary.log("Call with @Jk and @jk", "2025-03-01");
+
+    assert_eq!(diary.names("people"), vec!["Jane", "JohnK"]);
+}
+
+#[test]
+fn emails_urls_and_code_are_not_markers() {
+    let diary = TestDiary::new();
+    diary.log(
+        "Mailed bob@example.com about https://x.org/#faq, ran `grep #todo`, \\#42 is 100%done #real",
+        "2025-03-01",
+    );
+
+    assert!(diary.names("people").is_empty());
+    assert!(diary.names("projects").is_empty());
+    assert_eq!(diary.names("tags"), vec!["real"]);
+}
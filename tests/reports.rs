@@ -0,0 +1,268 @@
+mod common;
+
+use common::TestDiary;
+use std::fs;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Reviewed the PR for %Dia with @Ann", "yesterday");
+    diary.log("Paired with @Bob on %Dia parser ~1h", "yesterday");
+    diary.log("Call with @Carl about %Web", "today");
+    diary.log("Wrote notes #writing", "today");
+    diary.log("Old news %Legacy", "2020-01-01");
+    diary
+}
+
+#[test]
+fn standup_groups_yesterday_and_today_by_project() {
+    let diary = seeded();
+    let out = diary.ok(&["report", "standup"]);
+
+    let yesterday = out.find("## Yesterday").unwrap();
+    let today = out.find("## Today").unwrap();
+    assert!(yesterday < today);
+    assert!(out[yesterday..today].contains(
+        "- **Dia** with Ann, Bob\n  - Reviewed the PR for %Dia with @Ann\n  - Paired with @Bob"
+    ));
+    assert!(out[today..].contains("- **Web** with Carl\n  - Call with @Carl about %Web"));
+    assert!(out[today..].contains("- **No project**\n  - Wrote notes #writing"));
+    assert!(!out.contains("Old news"));
+
+    let out = diary.ok(&["report", "standup", "--format", "text"]);
+    assert!(out.contains("Yesterday:\n  Dia (with Ann, Bob)\n    - Reviewed the PR"));
+    assert!(!diary
+        .run(&["report", "standup", "--period", "2020"])
+        .status
+        .success());
+}
+
+#[test]
+fn weekly_report_covers_the_period() {
+    let diary = seeded();
+    let out = diary.ok(&["report", "weekly", "--period", "2020-01"]);
+    assert!(out.starts_with("# Weekly report 2020-01-01 to 2020-01-31"));
+    assert!(out.contains("## Legacy\n\n- Wed 2020-01-01: Old news %Legacy"));
+    assert!(!out.contains("Dia"));
+
+    let out = diary.ok(&["report", "weekly", "--period", "last 7 days"]);
+    assert!(out.contains("People involved: Ann, Bob, Carl"));
+    assert!(out.contains("## Dia (1h)\n\nWith Ann, Bob"));
+
+    let out = diary.ok(&["report", "weekly", "--period", "2019"]);
+    assert!(out.contains("Nothing logged."));
+}
+
+#[test]
+fn templates_can_be_edited() {
+    let diary = seeded();
+    diary.ok(&["report", "standup"]);
+    let path = diary
+        .path()
+        .join("dia")
+        .join("templates")
+        .join("standup.md");
+    assert!(fs::read_to_string(&path)
+        .unwrap()
+        .contains("{% for section in sections %}"));
+
+    fs::write(
+        &path,
+        "{% for section in sections %}\n{{ section.title }}: {{ section.people }}\n{% for project in section.projects %}\n{% if not loop.first %}, {% endif %}{{ project.name }}\n{% endfor %}\n{% endfor %}\n",
+    )
+    .unwrap();
+    let out = diary.ok(&["report", "standup"]);
+    assert_eq!(
+        out,
+        "Yesterday: Ann, Bob\nDia\nToday: Carl\nWeb\n, No project\n"
+    );
+
+    fs::write(
+        &path,
+        "{% for section in sections %}\n{{ section.title }}\n",
+    )
+    .unwrap();
+    let output = diary.run(&["report", "standup"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("never closed with {% endfor %}"));
+}
+
+#[test]
+fn own_text_template_replaces_the_built_in() {
+    let diary = seeded();
+    let dir = diary.path().join("dia").join("templates");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("weekly.txt"), "{{ count }} entries\n").unwrap();
+
+    assert_eq!(diary.ok(&["report", "weekly"]), "4 entries\n");
+    assert!(!dir.join("weekly.md").exists());
+}
+
+#[test]
+fn user_templates_are_reports() {
+    let diary = TestDiary::new();
+    diary.log(
+        "Reviewed the API with @\"Anna Maria\" on %Dia ~30m",
+        "2025-03-03",
+    );
+    diary.log("Fixed %Dia parser [09:00-10:30] #bug", "2025-03-04");
+    diary.log("Release notes for %Web ~15m", "2025-03-04");
+    diary.log("Untimed thoughts", "2025-04-01");
+
+    let output = diary.run(&["report", "invoice"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("available reports: time, standup, weekly"));
+
+    let dir = diary.path().join("dia").join("templates");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("invoice.md"),
+        r#"# Invoice {{ from | date:"%B %Y" }}
+{% for project in projects | sort:minutes | reverse %}
+
+## {{ project.name | upper }} ({{ project.count }} entries)
+{% for day in project.days %}
+- {{ day.date | date:"%d.%m." }} {{ day.weekday }}: {{ day.time }} {{ day.tags | join:" / " | default:"-" }}
+{% endfor %}
+{% endfor %}
+
+{% for day in days %}
+{{ day.date }}: {{ day.projects | length }} projects, {{ day.people | default:"nobody" }}
+{% endfor %}
+{% for entry in entries %}
+* {{ entry.text }}
+{% endfor %}
+
+Total: {{ minutes | hours | round:2 }} h = {{ minutes | hours | times:85 | round:2 }} EUR ({{ entries | sum:minutes | time }})
+"#,
+    )
+    .unwrap();
+
+    let out = diary.ok(&["report", "invoice", "--period", "2025-03"]);
+    assert_eq!(
+        out,
+        "# Invoice March 2025
+
+## DIA (2 entries)
+- 03.03. Mon: 30m -
+- 04.03. Tue: 1h 30m bug
+
+## WEB (1 entries)
+- 04.03. Tue: 15m -
+
+2025-03-03: 1 projects, Anna Maria
+2025-03-04: 2 projects, nobody
+* Reviewed the API with Anna Maria on Dia
+* Fixed Dia parser
+* Release notes for Web
+
+Total: 2.25 h = 191.25 EUR (2h 15m)
+"
+    );
+
+    let output = diary.run(&["report", "invoice", "--format", "text"]);
+    assert!(!output.status.success());
+
+    fs::write(dir.join("broken.txt"), "{{ entries | shout }}").unwrap();
+    let output = diary.run(&["report", "broken"]);
+    assert!(String::from_utf8_lossy(&output.stderr).contains("unknown filter 'shout'"));
+    assert!(!diary.run(&["report", "../broken"]).status.success());
+}
+
+#[test]
+fn brag_document_lists_achievements_by_project() {
+    let diary = TestDiary::new();
+    diary.log(
+        "Shipped the parser rewrite for %Dia with @Ann #shipped",
+        "2025-02-03",
+    );
+    diary.log("Routine upkeep on %Dia", "2025-02-04");
+    diary.log(
+        "Cut p95 latency of %Web in half with @Bob and @Ann ~2h #win/perf",
+        "2025-05-10",
+    );
+    diary.log("Mentored @Carl through the #win onboarding", "2025-06-01");
+    diary.log("Launched %Dia v2 #shipped", "2025-09-20");
+    diary.log("Old %Legacy release #shipped", "2024-12-31");
+
+    let out = diary.ok(&["report", "brag", "--period", "2025"]);
+    assert_eq!(
+        out,
+        "# What I achieved, 2025-01-01 to 2025-12-31
+
+## Dia
+
+Achievements: 2, from 2025-02-03 to 2025-09-20, with Ann
+
+- 2025-02-03: Shipped the parser rewrite for Dia with Ann
+- 2025-09-20: Launched Dia v2
+
+## Web
+
+Achievements: 1, from 2025-05-10 to 2025-05-10, with Ann, Bob
+
+- 2025-05-10: Cut p95 latency of Web in half with Bob and Ann
+
+## No project
+
+Achievements: 1, from 2025-06-01 to 2025-06-01, with Carl
+
+- 2025-06-01: Mentored Carl through the win onboarding
+
+## Collaborators
+
+Ann, Bob, Carl
+"
+    );
+
+    let out = diary.ok(&["report", "brag", "--period", "2023"]);
+    assert!(out.contains("Nothing tagged #win, #shipped in this period."));
+
+    // The achievement tags are configurable.
+    let config = diary.path().join("dia").join("config.toml");
+    let mut settings = fs::read_to_string(&config).unwrap();
+    settings = settings.replace(
+        "achievement_tags = [\"win\", \"shipped\"]",
+        "achievement_tags = [\"perf\"]",
+    );
+    fs::write(&config, settings).unwrap();
+    diary.log("Tuned the cache #perf", "2025-07-01");
+    let out = diary.ok(&["report", "brag", "--period", "2025"]);
+    assert!(out.contains("Tuned the cache"));
+    assert!(!out.contains("Launched"));
+
+    assert!(!diary
+        .run(&["report", "brag", "--format", "text"])
+        .status
+        .success());
+}
+
+#[test]
+fn bad_filter_arguments_are_errors() {
+    let diary = TestDiary::new();
+    let dir = diary.path().join("dia").join("templates");
+    fs::create_dir_all(&dir).unwrap();
+
+    for (template, message) in [
+        (
+            r#"{{ from | date:"%Q" }}"#,
+            "'%Q' is not a valid date format",
+        ),
+        (
+            r#"{{ from | date:"%H:%M" }}"#,
+            "'%H:%M' is not a valid date format",
+        ),
+        (
+            "{{ minutes | round:4000000000 }}",
+            "'round' needs a number of decimals up to 15",
+        ),
+    ] {
+        fs::write(dir.join("bad.txt"), template).unwrap();
+        let output = diary.run(&["report", "bad"]);
+        assert_eq!(output.status.code(), Some(1));
+        assert!(String::from_utf8_lossy(&output.stderr).contains(message));
+    }
+
+    fs::write(dir.join("bad.txt"), "{{ minutes | round:15 }}").unwrap();
+    assert_eq!(diary.ok(&["report", "bad"]).trim(), "0.000000000000000");
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+use std::io::Write;
+use std::process::Stdio;
+
+fn review(diary: &TestDiary, args: &[&str], input: &str) -> String {
+    let mut child = diary
+        .command()
+        .arg("review")
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn schedule(diary: &TestDiary, entry_id: i64) -> Option<(f64, i64, i64)> {
+    Connection::open(diary.db_path())
+        .unwrap()
+        .query_row(
+            "SELECT ease, interval_days, repetitions FROM review_state WHERE entry_id = ?",
+            [entry_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok()
+}
+
+/// Pretends the entry's next review is today again.
+fn make_due(diary: &TestDiary, entry_id: i64) {
+    Connection::open(diary.db_path())
+        .unwrap()
+        .execute(
+            "UPDATE review_state SET due_date = '2000-01-01' WHERE entry_id = ?",
+            [entry_id],
+        )
+        .unwrap();
+}
+
+#[test]
+fn nothing_due_in_an_empty_diary() {
+    let diary = TestDiary::new();
+    assert!(review(&diary, &[], "").contains("No entries due for review."));
+}
+
+#[test]
+fn graded_entries_follow_sm2_intervals() {
+    let diary = TestDiary::new();
+    diary.log("Lesson: always write a migration test", "2025-01-10");
+
+    let out = review(&diary, &[], "5\n");
+    assert!(out.contains("Lesson: always write a migration test"));
+    assert!(out.contains("Next review in 1 day(s)."));
+    assert_eq!(schedule(&diary, 1), Some((2.6, 1, 1)));
+    assert!(review(&diary, &[], "").contains("No entries due for review."));
+
+    make_due(&diary, 1);
+    review(&diary, &[], "5\n");
+    assert_eq!(schedule(&diary, 1).map(|s| (s.1, s.2)), Some((6, 2)));
+
+    make_due(&diary, 1);
+    review(&diary, &[], "5\n");
+    assert_eq!(schedule(&diary, 1).map(|s| (s.1, s.2)), Some((16, 3)));
+
+    make_due(&diary, 1);
+    review(&diary, &[], "1\n");
+    let (ease, interval, repetitions) = schedule(&diary, 1).unwrap();
+    assert_eq!((interval, repetitions), (1, 0));
+    assert!(ease < 2.8);
+
+    let last_reviewed: Option<String> = Connection::open(diary.db_path())
+        .unwrap()
+        .query_row(
+            "SELECT last_reviewed FROM entries WHERE id = 1",
+            [],
+            |row| row.get(0),
+        )
+        .unwrap();
+    assert!(last_reviewed.is_some());
+}
+
+#[test]
+fn skip_quit_and_invalid_input() {
+    let diary = TestDiary::new();
+    diary.log("First", "2025-01-01");
+    diary.log("Second", "2025-01-02");
+    diary.log("Third", "2025-01-03");
+
+    let out = review(&diary, &[], "s\nseven\n4\nq\n");
+    assert!(out.contains("Please enter a number from 0 to 5"));
+    assert!(out.contains("Reviewed 1 of 3 entries."));
+    assert_eq!(schedule(&diary, 3), None);
+    assert_eq!(schedule(&diary, 1), None);
+    assert!(schedule(&diary, 2).is_some());
+}
+
+#[test]
+fn restricts_review_to_project_tag_and_limit() {
+    let diary = TestDiary::new();
+    diary.log("Retro on %Dia #lesson", "2025-01-01");
+    diary.log("Retro on %Website #lesson", "2025-01-02");
+    diary.log("Standup for %Dia", "2025-01-03");
+
+    let out = review(&diary, &["--project", "Dia", "--tag", "lesson"], "q\n");
+    assert!(out.contains("1 entries due"));
+    assert!(out.contains("Retro on %Dia"));
+
+    let out = review(&diary, &["--tag", "lesson"], "q\n");
+    assert!(out.contains("2 entries due"));
+
+    let out = review(&diary, &["--limit", "1"], "q\n");
+    assert!(out.contains("1 entries due"));
+}
@@ -0,0 +1,133 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Paired with @Alice on %Dia #parser", "2025-03-01");
+    diary.log("Reviewed %Dia roadmap with @Bob #planning", "2025-03-02");
+    diary.log("Fixed login bug in %Website with @Alice #bug", "2025-03-03");
+    diary.log("Wrote quarterly report", "2025-03-05");
+    diary
+}
+
+#[test]
+fn lists_all_entries_without_filters() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries"]);
+    assert!(out.contains("Paired with @Alice"));
+    assert!(out.contains("Reviewed %Dia roadmap"));
+    assert!(out.contains("Fixed login bug"));
+    assert!(out.contains("Wrote quarterly report"));
+}
+
+#[test]
+fn filters_by_single_date() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--date", "2025-03-02"]);
+    assert!(out.contains("Reviewed %Dia roadmap"));
+    assert!(!out.contains("Paired with"));
+    assert!(!out.contains("Fixed login bug"));
+}
+
+#[test]
+fn filters_by_inclusive_date_range() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--date", "2025-03-02..2025-03-03"]);
+    assert!(out.contains("Reviewed %Dia roadmap"));
+    assert!(out.contains("Fixed login bug"));
+    assert!(!out.contains("Paired with"));
+    assert!(!out.contains("quarterly"));
+}
+
+#[test]
+fn rejects_malformed_dates() {
+    let diary = seeded();
+    assert!(!diary
+        .run(&["show", "entries", "--date", "someday"])
+        .status
+        .success());
+    assert!(!diary
+        .run(&["show", "entries", "--date", "2025-03-05..2025-03-01"])
+        .status
+        .success());
+}
+
+#[test]
+fn filters_by_full_text_search() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--search", "roadmap"]);
+    assert!(out.contains("Reviewed %Dia roadmap"));
+    assert!(!out.contains("Paired with"));
+
+    // The FTS index uses the porter stemmer.
+    let out = diary.ok(&["show", "entries", "--search", "reports"]);
+    assert!(out.contains("Wrote quarterly report"));
+}
+
+#[test]
+fn search_text_is_not_query_syntax() {
+    let diary = TestDiary::new();
+    diary.log("Sent the follow-up about %Dia", "2025-03-01");
+    diary.log("Said I don't \"do\" mornings", "2025-03-02");
+    diary.log("Follow the plan, then up the ante", "2025-03-03");
+
+    let out = diary.ok(&["show", "entries", "--search", "follow-up"]);
+    assert!(out.contains("Sent the follow-up"));
+    assert!(!out.contains("the ante"));
+
+    let out = diary.ok(&["show", "entries", "--search", "don't"]);
+    assert!(out.contains("mornings"));
+    let out = diary.ok(&["show", "entries", "--search", "\"do\" AND"]);
+    assert!(out.contains("No entries found"));
+    let out = diary.ok(&["show", "entries", "--search", "\"do\" mornings"]);
+    assert!(out.contains("mornings"));
+}
+
+#[test]
+fn filters_by_person_project_and_tag() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "entries", "--person", "Alice"]);
+    assert!(out.contains("Paired with"));
+    assert!(out.contains("Fixed login bug"));
+    assert!(!out.contains("Reviewed %Dia roadmap"));
+
+    let out = diary.ok(&["show", "entries", "--project", "%Dia"]);
+    assert!(out.contains("Paired with"));
+    assert!(out.contains("Reviewed %Dia roadmap"));
+    assert!(!out.contains("Fixed login bug"));
+
+    let out = diary.ok(&["show", "entries", "--tag", "bug"]);
+    assert!(out.contains("Fixed login bug"));
+    assert!(!out.contains("Paired with"));
+}
+
+#[test]
+fn combines_filters() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "entries", "--person", "Alice", "--project", "Dia"]);
+    assert!(out.contains("Paired with"));
+    assert!(!out.contains("Fixed login bug"));
+
+    let out = diary.ok(&[
+        "show",
+        "entries",
+        "--person",
+        "@Alice",
+        "--date",
+        "2025-03-02..2025-03-05",
+        "--search",
+        "login",
+    ]);
+    assert!(out.contains("Fixed login bug"));
+    assert!(!out.contains("Paired with"));
+}
+
+#[test]
+fn reports_when_nothing_matches() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--person", "Carol"]);
+    assert!(out.contains("No entries found matching your criteria."));
+}
@@ -0,0 +1,119 @@
+mod common;
+
+use common::TestDiary;
+use serde_json::Value;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    diary.log("Paired with @Alice, @Bob on %Dia #parser", "2025-03-01");
+    diary.log("Deployed %Website with @Alice", "2025-03-02");
+    diary
+}
+
+#[test]
+fn entries_as_json_include_relations() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--format", "json"]);
+    let entries: Value = serde_json::from_str(&out).unwrap();
+    let entries = entries.as_array().unwrap();
+
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0]["id"], 1);
+    assert_eq!(entries[0]["date"], "2025-03-01");
+    assert_eq!(
+        entries[0]["content"],
+        "Paired with @Alice, @Bob on %Dia #parser"
+    );
+    assert_eq!(entries[0]["people"], serde_json::json!(["Alice", "Bob"]));
+    assert_eq!(entries[0]["projects"], serde_json::json!(["Dia"]));
+    assert_eq!(entries[0]["tags"], serde_json::json!(["parser"]));
+    assert!(entries[0]["created_at"].is_string());
+}
+
+#[test]
+fn entries_as_jsonl_respect_filters() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--project", "Website", "-f", "jsonl"]);
+    let lines = out.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 1);
+    let entry: Value = serde_json::from_str(lines[0]).unwrap();
+    assert_eq!(entry["projects"], serde_json::json!(["Website"]));
+}
+
+#[test]
+fn empty_results_are_valid_json() {
+    let diary = TestDiary::new();
+    let out = diary.ok(&["show", "entries", "--format", "json"]);
+    assert_eq!(
+        serde_json::from_str::<Value>(&out).unwrap(),
+        serde_json::json!([])
+    );
+}
+
+#[test]
+fn entries_as_csv_quote_and_join_lists() {
+    let diary = seeded();
+    let out = diary.ok(&["show", "entries", "--format", "csv"]);
+    let lines = out.lines().collect::<Vec<_>>();
+
+    assert_eq!(
+        lines[0],
+        "id,date,created_at,content,projects,tags,people,minutes"
+    );
+    assert!(lines[1].starts_with("1,2025-03-01,"));
+    assert!(lines[1].contains("\"Paired with @Alice, @Bob on %Dia #parser\""));
+    assert!(lines[1].ends_with(",Dia,parser,Alice;Bob,"));
+}
+
+#[test]
+fn entity_lists_include_usage_counts() {
+    let diary = seeded();
+
+    let people: Value =
+        serde_json::from_str(&diary.ok(&["show", "people", "--format", "json"])).unwrap();
+    assert_eq!(people[0]["name"], "Alice");
+    assert_eq!(people[0]["entries"], 2);
+    assert_eq!(people[1]["name"], "Bob");
+    assert_eq!(people[1]["entries"], 1);
+
+    let out = diary.ok(&["show", "projects", "--format", "csv"]);
+    assert_eq!(
+        out.lines().collect::<Vec<_>>(),
+        [
+            "id,name,entries,first_seen,last_used,archived,metadata",
+            "1,Dia,1,2025-03-01,2025-03-01,false,",
+            "2,Website,1,2025-03-02,2025-03-02,false,"
+        ]
+    );
+
+    let out = diary.ok(&["show", "tags", "--format", "jsonl"]);
+    assert_eq!(out.lines().count(), 1);
+}
+
+#[test]
+fn dashboards_as_json_and_csv() {
+    let diary = seeded();
+
+    let out = diary.ok(&["show", "person", "@Alice", "--format", "json"]);
+    let dashboard: Value = serde_json::from_str(&out).unwrap();
+    assert_eq!(dashboard[0]["kind"], "person");
+    assert_eq!(dashboard[0]["name"], "Alice");
+    assert_eq!(dashboard[0]["entries"], 2);
+    assert_eq!(dashboard[0]["first_mention"], "2025-03-01");
+    assert_eq!(dashboard[0]["monthly"]["2025-03"], 2);
+    assert_eq!(dashboard[0]["with_people"][0]["name"], "Bob");
+    assert_eq!(dashboard[0]["latest"][0]["id"], 2);
+
+    let out = diary.ok(&["show", "project", "Dia", "--format", "csv"]);
+    assert_eq!(
+        out.lines().collect::<Vec<_>>(),
+        [
+            "kind,name,archived,metadata,first_mention,last_mention,entries,active_days,monthly,with_people,with_projects,with_tags,latest",
+            "project,Dia,false,,2025-03-01,2025-03-01,1,1,2025-03=1,Alice=1;Bob=1,,parser=1,1"
+        ]
+    );
+
+    let out = diary.ok(&["show", "tag", "parser", "--format", "jsonl"]);
+    assert_eq!(out.lines().count(), 1);
+}
@@ -0,0 +1,84 @@
+mod common;
+
+use common::TestDiary;
+
+fn seeded() -> TestDiary {
+    let diary = TestDiary::new();
+    for day in ["2025-03-03", "2025-03-04", "2025-03-05", "2025-03-07"] {
+        diary.log("Worked on %Dia with @Alice #dev", day);
+    }
+    diary.log("Call with @Bob about %Website", "2025-03-07");
+    diary.log("Old note", "2024-12-01");
+    diary
+}
+
+/// Finds the table row starting with `label` and returns its cells.
+fn row<'a>(out: &'a str, label: &str) -> Vec<&'a str> {
+    out.lines()
+        .map(|line| {
+            line.trim_matches('|')
+                .split('|')
+                .map(str::trim)
+                .collect::<Vec<_>>()
+        })
+        .find(|cells| cells.first() == Some(&label))
+        .unwrap_or_else(|| panic!("no row '{}' in:\n{}", label, out))
+}
+
+#[test]
+fn empty_diary_has_no_stats() {
+    let diary = TestDiary::new();
+    assert!(diary
+        .ok(&["stats"])
+        .contains("No entries found for this period."));
+}
+
+#[test]
+fn summarises_period() {
+    let diary = seeded();
+    let out = diary.ok(&["stats", "--period", "2025-03-01..2025-03-07"]);
+
+    assert_eq!(row(&out, "Entries")[1], "5");
+    assert_eq!(row(&out, "Active days")[1], "4 of 7");
+    assert_eq!(row(&out, "Longest streak")[1], "3 days");
+    assert_eq!(row(&out, "Current streak")[1], "1 days");
+    assert_eq!(row(&out, "2025-03-07")[1], "2");
+    assert_eq!(row(&out, "2025-03-06")[1], "0");
+    assert_eq!(row(&out, "Fri")[1], "2");
+    assert_eq!(row(&out, "Mon")[1], "1");
+}
+
+#[test]
+fn ranks_entities_and_project_share() {
+    let diary = seeded();
+    let out = diary.ok(&["stats", "--period", "2025-03-01..2025-03-31"]);
+
+    assert_eq!(row(&out, "Alice")[1], "4");
+    assert_eq!(row(&out, "Bob")[1], "1");
+    assert_eq!(row(&out, "dev")[1], "4");
+    assert!(out.contains("80.0%"));
+    assert!(out.contains("20.0%"));
+
+    let out = diary.ok(&["stats", "--period", "2025-03-01..2025-03-31", "--top", "1"]);
+    assert!(!out.contains("| Bob"));
+}
+
+#[test]
+fn long_periods_are_bucketed_by_iso_week() {
+    let diary = seeded();
+    let out = diary.ok(&["stats"]);
+
+    assert_eq!(row(&out, "Entries")[1], "6");
+    assert_eq!(row(&out, "2025-W10")[1], "5");
+    assert_eq!(row(&out, "2024-W48")[1], "1");
+}
+
+#[test]
+fn charts_are_optional() {
+    let diary = seeded();
+    let plain = diary.ok(&["stats", "--period", "2025-03-01..2025-03-07"]);
+    assert!(!plain.contains('█'));
+
+    let charted = diary.ok(&["stats", "--period", "2025-03-01..2025-03-07", "--chart"]);
+    assert!(charted.contains('█'));
+}
@@ -0,0 +1,65 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+fn minutes(diary: &TestDiary) -> Vec<Option<i64>> {
+    let conn = Connection::open(diary.db_path()).unwrap();
+    let mut stmt = conn
+        .prepare("SELECT minutes FROM entries ORDER BY id")
+        .unwrap();
+    let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+    rows.map(Result::unwrap).collect()
+}
+
+#[test]
+fn duration_markers_are_stored_per_entry() {
+    let diary = TestDiary::new();
+    diary.log("Deploy %web ~1h30m", "2025-03-03");
+    diary.log("Standup [09:00-09:15] and review ~1.5h", "2025-03-03");
+    diary.log("Night shift [23:30 - 00:30]", "2025-03-03");
+    diary.log(
+        "Approx. ~ an hour, see [25:00-26:00] and a~2h",
+        "2025-03-03",
+    );
+    assert_eq!(minutes(&diary), vec![Some(90), Some(105), Some(60), None]);
+
+    // Durations too long to count are ignored rather than overflowing.
+    diary.log(
+        "x ~99999999999999999999h ~99999999999999999999m ~4611686018427387904m ~4611686018427387904m ~1h",
+        "2025-03-03",
+    );
+    assert_eq!(minutes(&diary)[4], Some(4611686018427387904 + 60));
+
+    // Edits recompute the time.
+    diary.ok(&["edit", "1", "Deploy %web ~45m"]);
+    assert_eq!(minutes(&diary)[0], Some(45));
+}
+
+#[test]
+fn stats_and_report_break_time_down() {
+    let diary = TestDiary::new();
+    diary.log("Deploy %client/web with @ann ~1h30m #ops", "2025-03-03");
+    diary.log(
+        "Sync on %client/mobile with @ann @bob [09:00-10:00]",
+        "2025-03-04",
+    );
+    diary.log("Triage %other ~30m", "2025-03-05");
+    diary.log("Untimed %other", "2025-03-05");
+
+    let out = diary.ok(&["stats", "--period", "2025-03"]);
+    assert!(out.contains("| Tracked time           | 3h      |"));
+    assert!(out.contains("| ann             | 2h 30m | 83.3% |"));
+    assert!(out.contains("| client/web       | 1h 30m | 50.0% |"));
+
+    let out = diary.ok(&["report", "time", "--period", "2025-03", "--rollup"]);
+    assert!(out.starts_with("Time from 2025-03-01 to 2025-03-31: 3h"));
+    assert!(out.contains("| client           | 2h 30m | 83.3% |"));
+    assert!(out.contains("| ops          | 1h 30m | 50.0% |"));
+    assert!(out.contains("| 2025-03-05 |    30m | Triage %other ~30m"));
+    assert!(!out.contains("Untimed"));
+
+    let out = diary.ok(&["report", "time", "--period", "2024"]);
+    assert!(out.contains("No time tracked"));
+    assert!(!diary.run(&["report", "nonsense"]).status.success());
+}
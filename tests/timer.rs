@@ -0,0 +1,75 @@
+mod common;
+
+use common::TestDiary;
+use rusqlite::Connection;
+
+/// Moves the timer's clock back, as if it had been started `minutes` ago and
+/// paused for `paused` of them.
+fn backdate(diary: &TestDiary, minutes: i64, paused: i64) {
+    Connection::open(diary.db_path())
+        .unwrap()
+        .execute(
+            "UPDATE timer SET started_at = datetime('now', ?), paused_seconds = ?",
+            rusqlite::params![format!("-{minutes} minutes"), paused * 60],
+        )
+        .unwrap();
+}
+
+#[test]
+fn stopping_logs_an_entry_with_the_time_worked() {
+    let diary = TestDiary::new();
+    assert!(diary.ok(&["status"]).contains("No timer running."));
+
+    diary.ok(&["start", "%Dia refactor parser with @Ann"]);
+    backdate(&diary, 100, 10);
+    assert!(diary
+        .ok(&["status"])
+        .contains("Running: %Dia refactor parser with @Ann"));
+
+    let out = diary.ok(&["stop"]);
+    assert!(out.contains("Logged: %Dia refactor parser with @Ann ~1h30m"));
+    assert!(diary.ok(&["status"]).contains("No timer running."));
+
+    let out = diary.ok(&["show", "entries", "--person", "Ann", "--format", "csv"]);
+    assert!(out.contains(",Dia,,Ann,90"));
+}
+
+#[test]
+fn pauses_are_not_counted() {
+    let diary = TestDiary::new();
+    diary.ok(&["start", "%Dia docs"]);
+    backdate(&diary, 45, 0);
+    assert!(diary.ok(&["pause"]).contains("Paused: %Dia docs"));
+    assert!(!diary.run(&["pause"]).status.success());
+
+    // Time spent paused is added once the timer resumes.
+    Connection::open(diary.db_path())
+        .unwrap()
+        .execute(
+            "UPDATE timer SET paused_at = datetime('now', '-15 minutes')",
+            [],
+        )
+        .unwrap();
+    assert!(diary.ok(&["resume"]).contains("Running: %Dia docs"));
+    assert!(!diary.run(&["resume"]).status.success());
+
+    assert!(diary.ok(&["stop"]).contains("%Dia docs ~30m"));
+}
+
+#[test]
+fn only_one_timer_runs_at_a_time() {
+    let diary = TestDiary::new();
+    assert!(!diary.run(&["stop"]).status.success());
+    assert!(!diary.run(&["pause"]).status.success());
+
+    diary.ok(&["start", "First"]);
+    let output = diary.run(&["start", "Second"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("already running for 'First'"));
+
+    assert!(diary
+        .ok(&["stop", "--discard"])
+        .contains("Timer discarded."));
+    assert!(diary.ok(&["show", "entries"]).contains("No entries found"));
+    diary.ok(&["start", "Second"]);
+}